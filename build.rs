@@ -1,7 +1,12 @@
+extern crate globset;
 extern crate ignore;
 extern crate json5;
+extern crate regex;
+#[macro_use]
+extern crate serde;
 extern crate serde_json;
 
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fs;
 use std::path::Path;
@@ -10,6 +15,21 @@ use std::{cmp, env, error};
 use ignore::Walk;
 use serde_json::Value;
 
+// Shared with the runtime, so that the raw templates accepted here are exactly
+// the ones that tokei can count.
+#[allow(dead_code)]
+#[path = "src/language/raw_template.rs"]
+mod raw_template;
+
+// Shared with the runtime, so that globs are checked as they're built there.
+#[path = "src/utils/glob.rs"]
+mod glob;
+
+// Shared with the runtime, so that the categories are only listed once.
+#[allow(dead_code)]
+#[path = "src/category.rs"]
+mod category;
+
 fn main() -> Result<(), Box<dyn error::Error>> {
     let out_dir = env::var_os("OUT_DIR").expect("No OUT_DIR variable.");
     generate_languages(&out_dir)?;
@@ -46,6 +66,278 @@ fn generate_languages(out_dir: &OsStr) -> Result<(), Box<dyn error::Error>> {
         sort_prop!("multi_line");
     }
 
+    // An extension claimed by more than one language would generate an
+    // unreachable match arm, so only the first language to claim it is
+    // matched against it.
+    let mut seen_extensions = HashSet::new();
+    for (_key, item) in json
+        .get_mut("languages")
+        .unwrap()
+        .as_object_mut()
+        .unwrap()
+        .iter_mut()
+    {
+        let unique_extensions: Vec<Value> = item
+            .get("extensions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|e| seen_extensions.insert(e.as_str().unwrap().to_owned()))
+            .cloned()
+            .collect();
+
+        item.as_object_mut()
+            .unwrap()
+            .insert("unique_extensions".into(), unique_extensions.into());
+    }
+
+    // Names are matched lowercase against the languages' names first, and
+    // then against their aliases, skipping any that are already taken.
+    let mut seen_aliases = HashSet::new();
+    let languages = json["languages"].as_object_mut().unwrap();
+    let mut unique_aliases: Vec<Vec<Value>> = languages
+        .iter()
+        .map(|(key, item)| {
+            let name = item.get("name").and_then(Value::as_str).unwrap_or(key);
+            [key.to_lowercase(), name.to_lowercase()]
+                .into_iter()
+                .filter(|alias| seen_aliases.insert(alias.clone()))
+                .map(Value::from)
+                .collect()
+        })
+        .collect();
+    for ((_key, item), unique_aliases) in languages.iter_mut().zip(&mut unique_aliases) {
+        unique_aliases.extend(
+            item.get("aliases")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter(|alias| seen_aliases.insert(alias.as_str().unwrap().to_lowercase()))
+                .cloned(),
+        );
+    }
+    for ((_key, item), unique_aliases) in languages.iter_mut().zip(unique_aliases) {
+        item.as_object_mut()
+            .unwrap()
+            .insert("unique_aliases".into(), unique_aliases.into());
+    }
+
+    // Interpreters are the programs run by the `shebangs`, along with the
+    // ones run through `env`. Like extensions, each can only belong to one
+    // language.
+    let mut seen_interpreters = HashSet::new();
+    for (_key, item) in json["languages"].as_object_mut().unwrap().iter_mut() {
+        let shebangs = item.get("shebangs").and_then(Value::as_array);
+        let env = item.get("env").and_then(Value::as_array);
+        let unique_interpreters: Vec<Value> = shebangs
+            .into_iter()
+            .flatten()
+            .filter_map(|shebang| shebang_interpreter(shebang.as_str().unwrap()))
+            .chain(env.into_iter().flatten().map(|e| e.as_str().unwrap()))
+            .filter(|interpreter| seen_interpreters.insert(interpreter.to_string()))
+            .map(Value::from)
+            .collect();
+
+        item.as_object_mut()
+            .unwrap()
+            .insert("unique_interpreters".into(), unique_interpreters.into());
+    }
+
+    // Raw strings and comments can only be skipped over by the quick scan for
+    // important syntax if it looks for part of their opening delimiter.
+    for (key, item) in json["languages"].as_object_mut().unwrap().iter_mut() {
+        let raw_quote_syntax: Vec<Value> = ["raw_quotes", "raw_comments"]
+            .iter()
+            .filter_map(|prop| item.get(prop).and_then(Value::as_array))
+            .flatten()
+            .map(|raw| {
+                match raw_template::parse(raw[0].as_str().unwrap(), raw[1].as_str().unwrap()) {
+                    Ok((start, _)) => Value::from(start.important_syntax()),
+                    Err(error) => panic!("The raw template {raw} of {key} {error}."),
+                }
+            })
+            .collect();
+
+        item.as_object_mut()
+            .unwrap()
+            .insert("raw_quote_syntax".into(), raw_quote_syntax.into());
+    }
+
+    // Catch invalid globs and categories here, rather than when they're
+    // first used. Doc comments are only found within the comments that the
+    // parser already knows about, so each must start like one of them.
+    for (key, item) in json["languages"].as_object().unwrap() {
+        let strings = |prop: &str, index: Option<usize>| -> Vec<String> {
+            item.get(prop)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .map(|value| match index {
+                    Some(index) => value[index].as_str().unwrap().to_owned(),
+                    None => value.as_str().unwrap().to_owned(),
+                })
+                .collect()
+        };
+        let line_comments = strings("line_comment", None);
+        for doc in strings("doc_line_comment", None) {
+            if !line_comments.iter().any(|c| doc.starts_with(c.as_str())) {
+                panic!("The doc comment {doc:?} of {key} doesn't start with a line comment.");
+            }
+        }
+        let multi_line_comments: Vec<String> = strings("multi_line_comments", Some(0))
+            .into_iter()
+            .chain(strings("nested_comments", Some(0)))
+            .collect();
+        for doc in strings("doc_multi_line_comments", Some(0)) {
+            if !multi_line_comments
+                .iter()
+                .any(|c| doc.starts_with(c.as_str()))
+            {
+                panic!("The doc comment {doc:?} of {key} doesn't start with a multi line comment.");
+            }
+        }
+
+        let quotes: Vec<String> = strings("quotes", Some(0))
+            .into_iter()
+            .chain(strings("verbatim_quotes", Some(0)))
+            .chain(strings("doc_quotes", Some(0)))
+            .collect();
+        for quote in strings("interpolations", Some(0)) {
+            if !quotes.contains(&quote) {
+                panic!("The interpolation in {quote:?} of {key} isn't in one of its quotes.");
+            }
+        }
+
+        if let Some(category) = item.get("category") {
+            if let Err(error) = category.as_str().unwrap().parse::<category::Category>() {
+                panic!("{error} in {key}");
+            }
+        }
+
+        for glob in item
+            .get("globs")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if let Err(error) = glob::glob(glob.as_str().unwrap(), true) {
+                panic!("Invalid glob in {key}: {error}");
+            }
+        }
+    }
+
+    // Languages that are only reachable through the heuristics of an
+    // extension still list it, so that it's shown in `tokei --languages`.
+    let heuristics = json.get("heuristics").cloned().unwrap_or_default();
+    for (extension, rules) in heuristics.as_object().into_iter().flatten() {
+        for rule in rules.as_array().unwrap() {
+            let name = rule["language"].as_str().unwrap();
+            let language = json["languages"]
+                .get_mut(name)
+                .unwrap_or_else(|| panic!("Unknown language {name:?} in heuristics."));
+            let extensions = language
+                .as_object_mut()
+                .unwrap()
+                .entry("extensions")
+                .or_insert_with(|| Value::Array(Vec::new()))
+                .as_array_mut()
+                .unwrap();
+
+            if !extensions.iter().any(|e| e == extension.as_str()) {
+                extensions.push(extension.as_str().into());
+            }
+        }
+    }
+
+    // Each language records the group it's in, for `LanguageType::group`.
+    let groups = json.get("groups").cloned().unwrap_or_default();
+    for (group, members) in groups.as_object().into_iter().flatten() {
+        let languages = json["languages"].as_object().unwrap();
+        if languages.iter().any(|(key, item)| {
+            key == group || item.get("name").and_then(Value::as_str) == Some(group)
+        }) {
+            panic!("The {group:?} group has the same name as a language.");
+        }
+
+        for name in members.as_array().unwrap() {
+            let name = name.as_str().unwrap();
+            let language = json["languages"]
+                .get_mut(name)
+                .unwrap_or_else(|| panic!("Unknown language {name:?} in group {group:?}."))
+                .as_object_mut()
+                .unwrap();
+
+            if let Some(other) = language.insert("group".into(), group.as_str().into()) {
+                panic!("{name} is in both the {other} and {group:?} groups.");
+            }
+        }
+    }
+
+    // Each embedding's rules are added to all of its languages, with the
+    // regex of their whole opening compiled here to catch any mistakes.
+    let embeddings = json.get("embeddings").cloned().unwrap_or_default();
+    for (embedding, definition) in embeddings.as_object().into_iter().flatten() {
+        let rules: Vec<Value> = definition["rules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|rule| {
+                let start = rule["start"].as_str().unwrap();
+                let pattern = rule.get("opening").and_then(Value::as_str).unwrap_or("");
+                let opening = format!("{}(?:{})", regex::escape(start), pattern);
+                if let Err(error) = regex::Regex::new(&opening) {
+                    panic!("Invalid opening in the {embedding:?} embedding: {error}");
+                }
+
+                let language = rule["language"].as_str().unwrap();
+                if json["languages"].get(language).is_none() {
+                    panic!("Unknown language {language:?} in the {embedding:?} embedding.");
+                }
+                let select = match rule.get("select").and_then(Value::as_str) {
+                    None => "Fixed",
+                    Some("mime") => "Mime",
+                    Some("name") => "Name",
+                    Some(select) => {
+                        panic!("Unknown select {select:?} in the {embedding:?} embedding.")
+                    }
+                };
+                let delimiter = match rule.get("delimiter").and_then(Value::as_str) {
+                    None | Some("code") => "Code",
+                    Some("comment") => "Comment",
+                    Some(delimiter) => {
+                        panic!("Unknown delimiter {delimiter:?} in the {embedding:?} embedding.")
+                    }
+                };
+
+                serde_json::json!({
+                    "start": start,
+                    "end": rule["end"],
+                    "opening": opening,
+                    "language": language,
+                    "select": select,
+                    "delimiter": delimiter,
+                    "skip_inline": rule.get("skip_inline").and_then(Value::as_bool).unwrap_or(false),
+                })
+            })
+            .collect();
+
+        for name in definition["languages"].as_array().unwrap() {
+            let name = name.as_str().unwrap();
+            json["languages"]
+                .get_mut(name)
+                .unwrap_or_else(|| {
+                    panic!("Unknown language {name:?} in the {embedding:?} embedding.")
+                })
+                .as_object_mut()
+                .unwrap()
+                .entry("embedding_rules")
+                .or_insert_with(|| Value::Array(Vec::new()))
+                .as_array_mut()
+                .unwrap()
+                .extend(rules.iter().cloned());
+        }
+    }
+
     let output_path = Path::new(&out_dir).join("language_type.rs");
     let rust_code = tera.render_str(
         &std::fs::read_to_string("src/language/language_type.tera.rs")?,
@@ -56,6 +348,19 @@ fn generate_languages(out_dir: &OsStr) -> Result<(), Box<dyn error::Error>> {
     Ok(())
 }
 
+/// The program run by one of the `shebangs` of `languages.json`, which are
+/// simple enough not to need the full parser in `src/language/shebang.rs`.
+fn shebang_interpreter(shebang: &str) -> Option<&str> {
+    let mut words = shebang.strip_prefix("#!")?.split_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+
+    if program == "env" {
+        words.next()
+    } else {
+        Some(program)
+    }
+}
+
 fn compare_json_str_len(a: &Value, b: &Value) -> cmp::Ordering {
     let a = a.as_array().expect("a as array");
     let b = b.as_array().expect("b as array");
@@ -127,7 +432,7 @@ use super::*;
         fn {0}() {{
             const _: &str = include_str!(r###"{2}"###);
             let mut languages = Languages::new();
-            languages.get_statistics(&["{1}"], &[], &Config::default());
+            languages.get_statistics(&["{1}"], &[], &test_config());
 
             if languages.len() != 1 {{
                 panic!("wrong languages detected: expected just {0}, found {{:?}}",
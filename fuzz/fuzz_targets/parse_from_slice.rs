@@ -7,6 +7,8 @@ use tokei::{Config, LanguageType};
 pub struct FuzzInput<'a> {
     lang: LanguageType,
     treat_doc_strings_as_comments: bool,
+    mixed: bool,
+    logical_lines: bool,
     data: &'a [u8],
 }
 
@@ -15,16 +17,29 @@ pub struct FuzzInput<'a> {
 pub fn parse_from_slice(input: FuzzInput, check_total: bool) {
     let config = &Config {
         treat_doc_strings_as_comments: Some(input.treat_doc_strings_as_comments),
+        mixed: Some(input.mixed),
+        logical_lines: Some(input.logical_lines),
 
         // these options don't impact the behaviour of parse_from_slice:
         columns: None,
         hidden: None,
+        vendored: None,
+        generated: None,
+        documentation: None,
         no_ignore: None,
         no_ignore_parent: None,
         no_ignore_dot: None,
         no_ignore_vcs: None,
         sort: None,
         types: None,
+        encoding: None,
+        encodings: None,
+        groups: None,
+        group_by: None,
+        categories: None,
+        exclude_categories: None,
+        total_programming_only: None,
+        languages: None,
         for_each_fn: None,
     };
 
@@ -0,0 +1,153 @@
+//! `tokei annotate`, which prints a file with how each of its lines is
+//! counted, to help find where a count goes wrong.
+
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    io::{self, Write},
+    path::Path,
+};
+
+use tokei::{Config, LanguageType, LineClass, LineKind};
+
+use crate::cli::Annotate;
+
+/// Marks lines that aren't counted, such as blank lines around embedded code.
+const UNCOUNTED: &str = "-";
+
+/// The marker of a line in the gutter, e.g. `C` for code, `C+M` for code with
+/// a comment, `D` for a doc comment, or `C[JavaScript]` for code of JavaScript
+/// embedded in HTML.
+fn marker(class: &LineClass) -> String {
+    let kind = match class.kind {
+        LineKind::Code if class.mixed => "C+M",
+        LineKind::Code => "C",
+        LineKind::Comment if class.doc => "D",
+        LineKind::Comment => "M",
+        LineKind::Blank => "B",
+        _ => "?",
+    };
+
+    match class.language {
+        Some(language) => format!("{}[{}]", kind, language),
+        None => kind.to_owned(),
+    }
+}
+
+pub fn print_annotated(annotate: &Annotate, config: &Config) -> Result<(), Box<dyn Error>> {
+    let mut writer = io::BufWriter::new(io::stdout().lock());
+    write_annotated(annotate, config, &mut writer)?;
+    Ok(writer.flush()?)
+}
+
+/// Writes the file with its markers, reading it and detecting its language the
+/// same way as it's counted, so that the markers add up to its counts.
+fn write_annotated(
+    annotate: &Annotate,
+    config: &Config,
+    writer: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(&annotate.file);
+    let language = match annotate.language {
+        Some(ref name) => name.parse::<LanguageType>()?,
+        None => LanguageType::from_file(path, config).ok_or_else(|| {
+            format!(
+                "Couldn't detect the language of {}, use --type to set it.",
+                path.display()
+            )
+        })?,
+    };
+
+    let text = LanguageType::read_text(path, config)
+        .map_err(|error| format!("Couldn't read {}: {}", path.display(), error))?;
+    let mut markers: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for class in language.classify_lines(&text, config) {
+        markers.entry(class.line).or_default().push(marker(&class));
+    }
+    let markers: BTreeMap<usize, String> = markers
+        .into_iter()
+        .map(|(line, markers)| (line, markers.join(" ")))
+        .collect();
+
+    let text = String::from_utf8_lossy(&text);
+    let lines: Vec<&str> = text.lines().collect();
+    let number_width = lines.len().to_string().len();
+    let marker_width = markers.values().map(String::len).max().unwrap_or(1);
+
+    for (i, line) in lines.into_iter().enumerate() {
+        let marker = markers.get(&(i + 1)).map_or(UNCOUNTED, String::as_str);
+        writeln!(
+            writer,
+            "{:>number_width$} {:<marker_width$} | {}",
+            i + 1,
+            marker,
+            line
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn annotated(dir: &TempDir, file: &str, language: Option<&str>) -> String {
+        let annotate = Annotate {
+            file: dir.path().join(file).to_string_lossy().into_owned(),
+            language: language.map(String::from),
+        };
+        let mut output = Vec::new();
+        write_annotated(&annotate, &Config::default(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn markers() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.rs"), "// Hello\nfn main() {}\n\n").unwrap();
+
+        assert_eq!(
+            annotated(&dir, "main.rs", None),
+            "1 M | // Hello\n2 C | fn main() {}\n3 B | \n"
+        );
+        assert_eq!(
+            annotated(&dir, "main.rs", Some("Python")),
+            "1 C | // Hello\n2 C | fn main() {}\n3 B | \n"
+        );
+    }
+
+    #[test]
+    fn files_are_read_as_they_are_counted() {
+        let dir = TempDir::new().unwrap();
+        let utf16: Vec<u8> = "\u{FEFF}# \u{e9}t\u{e9}\nx = 1\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        fs::write(dir.path().join("a.py"), utf16).unwrap();
+        fs::write(dir.path().join("b.inc"), "# x\nx = 1\n").unwrap();
+        fs::write(
+            dir.path().join(".gitattributes"),
+            "*.inc linguist-language=Python\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("video.py"), b"\x47\x40\x00\x10\x00\x00").unwrap();
+
+        assert_eq!(
+            annotated(&dir, "a.py", None),
+            "1 M | # \u{e9}t\u{e9}\n2 C | x = 1\n"
+        );
+        assert_eq!(annotated(&dir, "b.inc", None), "1 M | # x\n2 C | x = 1\n");
+
+        let annotate = Annotate {
+            file: dir.path().join("video.py").to_string_lossy().into_owned(),
+            language: None,
+        };
+        let error = write_annotated(&annotate, &Config::default(), &mut Vec::new()).unwrap_err();
+        assert!(error.to_string().contains("binary file"), "{}", error);
+    }
+}
@@ -0,0 +1,71 @@
+use std::{fmt, str::FromStr};
+
+use serde::de::{self, Deserialize, Deserializer};
+
+/// The broad category of a language, used to separate source code from
+/// markup, data, and prose when filtering and totalling languages.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Category {
+    /// General purpose and scripting languages, e.g. Rust or Bash.
+    #[default]
+    Programming,
+    /// Markup, stylesheet, and template languages, e.g. HTML or CSS.
+    Markup,
+    /// Data, configuration, and schema languages, e.g. JSON or TOML.
+    Data,
+    /// Languages primarily written as prose, e.g. Markdown.
+    Prose,
+}
+
+impl Category {
+    /// Every category, in the order they're listed in.
+    pub const ALL: &'static [Category] = &[
+        Category::Programming,
+        Category::Markup,
+        Category::Data,
+        Category::Prose,
+    ];
+
+    /// Returns the lowercase name of the category, as used in
+    /// `languages.json` and the configuration.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Programming => "programming",
+            Category::Markup => "markup",
+            Category::Data => "data",
+            Category::Prose => "prose",
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Category::ALL
+            .iter()
+            .copied()
+            .find(|category| category.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unsupported category: {}", s))
+    }
+}
+
+impl<'de> Deserialize<'de> for Category {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
@@ -2,17 +2,26 @@ use std::{process, str::FromStr};
 
 use clap::{crate_description, value_parser, Arg, ArgAction, ArgMatches};
 use colored::Colorize;
-use tokei::{Config, LanguageType, Sort};
+use tokei::{Category, Config, GroupBy, LanguageType, Sort};
 
 use crate::{
     cli_utils::{crate_version, parse_or_exit, NumberFormatStyle},
     consts::{
         BLANKS_COLUMN_WIDTH, CODE_COLUMN_WIDTH, COMMENTS_COLUMN_WIDTH, LANGUAGE_COLUMN_WIDTH,
-        LINES_COLUMN_WIDTH, PATH_COLUMN_WIDTH,
+        LINES_COLUMN_WIDTH, LOGICAL_COLUMN_WIDTH, MIXED_COLUMN_WIDTH, PATH_COLUMN_WIDTH,
     },
     input::Format,
 };
 
+/// The arguments of `tokei annotate`.
+#[derive(Debug)]
+pub struct Annotate {
+    pub file: String,
+    /// Kept as written until the config files are read, as the name may refer
+    /// to a language defined in them.
+    pub language: Option<String>,
+}
+
 /// Used for sorting languages.
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub enum Streaming {
@@ -40,6 +49,9 @@ pub struct Cli {
     pub columns: Option<usize>,
     pub files: bool,
     pub hidden: bool,
+    pub vendored: bool,
+    pub generated: bool,
+    pub documentation: bool,
     pub no_ignore: bool,
     pub no_ignore_parent: bool,
     pub no_ignore_dot: bool,
@@ -49,8 +61,18 @@ pub struct Cli {
     pub print_languages: bool,
     pub sort: Option<Sort>,
     pub sort_reverse: bool,
-    pub types: Option<Vec<LanguageType>>,
+    /// Kept as written until `override_config`, as the names may refer to
+    /// languages defined in the config files.
+    pub types: Option<Vec<String>>,
+    pub categories: Option<Vec<Category>>,
+    pub exclude_categories: Option<Vec<Category>>,
+    pub total_programming_only: bool,
+    pub group_by: Option<GroupBy>,
+    pub encoding: Option<String>,
+    pub annotate: Option<Annotate>,
     pub compact: bool,
+    pub mixed: bool,
+    pub logical_lines: bool,
     pub number_format: num_format::CustomFormat,
 }
 
@@ -105,6 +127,30 @@ impl Cli {
                     .action(ArgAction::SetTrue)
                     .help("Count hidden files."),
             )
+            .arg(
+                Arg::new("vendored")
+                    .long("vendored")
+                    .action(ArgAction::SetTrue)
+                    .help(
+                        "Count vendored files, such as those in vendor and node_modules \
+                        directories.",
+                    ),
+            )
+            .arg(
+                Arg::new("generated")
+                    .long("generated")
+                    .action(ArgAction::SetTrue)
+                    .help(
+                        "Count generated files, such as minified JavaScript and files \
+                        marked DO NOT EDIT.",
+                    ),
+            )
+            .arg(
+                Arg::new("documentation")
+                    .long("documentation")
+                    .action(ArgAction::SetTrue)
+                    .help("Count files marked with linguist-documentation in .gitattributes."),
+            )
             .arg(
                 Arg::new("input")
                     .num_args(1..)
@@ -117,7 +163,10 @@ impl Cli {
                     .short('l')
                     .action(ArgAction::SetTrue)
                     .conflicts_with("input")
-                    .help("Prints out supported languages and their extensions."),
+                    .help(
+                        "Prints out supported languages and their extensions. Use with \
+                        --output to print the full syntax of every language.",
+                    ),
             )
             .arg(Arg::new("no_ignore")
                 .long("no-ignore")
@@ -203,6 +252,51 @@ impl Cli {
                         -t=Rust,Markdown",
                     ),
             )
+            .arg(
+                Arg::new("category")
+                    .long("category")
+                    .action(ArgAction::Append)
+                    .help(
+                        "Filters output by language category, separated by a comma. One \
+                        of programming, markup, data, or prose. i.e. --category=programming",
+                    ),
+            )
+            .arg(
+                Arg::new("exclude_category")
+                    .long("exclude-category")
+                    .action(ArgAction::Append)
+                    .help(
+                        "Ignore languages in the given categories, separated by a comma. \
+                        i.e. --exclude-category=data,prose",
+                    ),
+            )
+            .arg(
+                Arg::new("total_programming_only")
+                    .long("total-programming-only")
+                    .action(ArgAction::SetTrue)
+                    .help(
+                        "Only count programming languages in the total, while still \
+                        listing markup, data, and prose languages.",
+                    ),
+            )
+            .arg(
+                Arg::new("group_by")
+                    .long("group-by")
+                    .value_parser(["language", "group"])
+                    .ignore_case(true)
+                    .help(
+                        "Count each group of languages, e.g. \"C family\", as a single row, \
+                        with a breakdown of its languages.",
+                    ),
+            )
+            .arg(
+                Arg::new("encoding")
+                    .long("encoding")
+                    .help(
+                        "The encoding of files without a byte order mark, e.g. Shift_JIS, GBK, or \
+                        windows-1252. Defaults to UTF-8, or UTF-16 when detected.",
+                    ),
+            )
             .arg(
                 Arg::new("compact")
                     .long("compact")
@@ -210,6 +304,25 @@ impl Cli {
                     .action(ArgAction::SetTrue)
                     .help("Do not print statistics about embedded languages."),
             )
+            .arg(
+                Arg::new("mixed")
+                    .long("mixed")
+                    .global(true)
+                    .action(ArgAction::SetTrue)
+                    .help(
+                        "Count lines with both code and a comment in a separate Mixed column. \
+                        These lines are still counted as code.",
+                    ),
+            )
+            .arg(
+                Arg::new("logical_lines")
+                    .long("logical-lines")
+                    .action(ArgAction::SetTrue)
+                    .help(
+                        "Count logical lines, i.e. statements, in a separate Logical column, for \
+                        languages that support it such as C and shell scripts.",
+                    ),
+            )
             .arg(
                 Arg::new("num_format_style")
                     .long("num-format")
@@ -234,11 +347,37 @@ impl Cli {
                         3: enable file level trace. Not recommended on multiple files",
                     ),
             )
+            .args_conflicts_with_subcommands(true)
+            .subcommand(
+                clap::Command::new("annotate")
+                    .about(
+                        "Prints a file with how each line is counted: C for code, M for \
+                        comments, and B for blanks, followed by the embedded language, e.g. \
+                        C[JavaScript].",
+                    )
+                    .arg(
+                        Arg::new("file")
+                            .required(true)
+                            .help("The path to the file to be annotated."),
+                    )
+                    .arg(
+                        Arg::new("type")
+                            .long("type")
+                            .short('t')
+                            .help(
+                                "The language to count the file as, instead of detecting it \
+                                from the path. i.e. -t=Rust",
+                            ),
+                    ),
+            )
             .get_matches();
 
         let columns = matches.get_one::<usize>("columns").cloned();
         let files = matches.get_flag("files");
         let hidden = matches.get_flag("hidden");
+        let vendored = matches.get_flag("vendored");
+        let generated = matches.get_flag("generated");
+        let documentation = matches.get_flag("documentation");
         let no_ignore = matches.get_flag("no_ignore");
         let no_ignore_parent = matches.get_flag("no_ignore_parent");
         let no_ignore_dot = matches.get_flag("no_ignore_dot");
@@ -246,15 +385,30 @@ impl Cli {
         let print_languages = matches.get_flag("languages");
         let verbose = matches.get_count("verbose") as u64;
         let compact = matches.get_flag("compact");
+        let mixed = matches.get_flag("mixed");
+        let logical_lines = matches.get_flag("logical_lines");
         let types = matches.get_many("types").map(|e| {
-            e.flat_map(|x: &String| {
-                x.split(',')
-                    .map(str::parse::<LanguageType>)
-                    .filter_map(Result::ok)
-                    .collect::<Vec<_>>()
-            })
-            .collect()
+            e.flat_map(|x: &String| x.split(',').map(String::from).collect::<Vec<_>>())
+                .collect()
+        });
+
+        let categories = matches.get_many("category").map(|e| {
+            e.flat_map(|x: &String| x.split(',').map(parse_or_exit).collect::<Vec<_>>())
+                .collect()
         });
+        let exclude_categories = matches.get_many("exclude_category").map(|e| {
+            e.flat_map(|x: &String| x.split(',').map(parse_or_exit).collect::<Vec<_>>())
+                .collect()
+        });
+        let total_programming_only = matches.get_flag("total_programming_only");
+        let group_by = matches.get_one::<String>("group_by").map(parse_or_exit);
+        let encoding = matches.get_one::<String>("encoding").cloned();
+        if let Some(ref label) = encoding {
+            if encoding_rs::Encoding::for_label(label.as_bytes()).is_none() {
+                eprintln!("Error:\nUnsupported encoding: {}", label);
+                process::exit(1);
+            }
+        }
 
         let num_format_style = matches
             .get_one::<String>("num_format_style")
@@ -279,6 +433,12 @@ impl Cli {
         // give a useful error to the user.
         let output = matches.get_one("output").cloned();
         let streaming = matches.get_one::<String>("streaming").map(parse_or_exit);
+        let annotate = matches
+            .subcommand_matches("annotate")
+            .map(|matches| Annotate {
+                file: matches.get_one::<String>("file").cloned().unwrap(),
+                language: matches.get_one::<String>("type").cloned(),
+            });
 
         crate::cli_utils::setup_logger(verbose);
 
@@ -287,6 +447,9 @@ impl Cli {
             columns,
             files,
             hidden,
+            vendored,
+            generated,
+            documentation,
             no_ignore,
             no_ignore_parent,
             no_ignore_dot,
@@ -297,7 +460,15 @@ impl Cli {
             sort,
             sort_reverse,
             types,
+            categories,
+            exclude_categories,
+            total_programming_only,
+            group_by,
+            encoding,
+            annotate,
             compact,
+            mixed,
+            logical_lines,
             number_format,
         };
 
@@ -391,11 +562,21 @@ impl Cli {
     ///
     /// #### Shared options
     /// * `hidden`
+    /// * `vendored`
+    /// * `generated`
+    /// * `documentation`
     /// * `no_ignore`
     /// * `no_ignore_parent`
     /// * `no_ignore_dot`
     /// * `no_ignore_vcs`
     /// * `types`
+    /// * `categories`
+    /// * `exclude_categories`
+    /// * `total_programming_only`
+    /// * `group_by`
+    /// * `encoding`
+    /// * `mixed`
+    /// * `logical_lines`
     pub fn override_config(&mut self, mut config: Config) -> Config {
         config.hidden = if self.hidden {
             Some(true)
@@ -403,6 +584,24 @@ impl Cli {
             config.hidden
         };
 
+        config.vendored = if self.vendored {
+            Some(true)
+        } else {
+            config.vendored
+        };
+
+        config.generated = if self.generated {
+            Some(true)
+        } else {
+            config.generated
+        };
+
+        config.documentation = if self.documentation {
+            Some(true)
+        } else {
+            config.documentation
+        };
+
         config.no_ignore = if self.no_ignore {
             Some(true)
         } else {
@@ -432,7 +631,7 @@ impl Cli {
                 println!("{}", serde_json::json!({"language": l.name(), "stats": e}));
             }),
             Some(Streaming::Simple) => Some(|l: LanguageType, e| {
-                println!(
+                print!(
                     "{:>LANGUAGE_COLUMN_WIDTH$} {:<PATH_COLUMN_WIDTH$} {:>LINES_COLUMN_WIDTH$} {:>CODE_COLUMN_WIDTH$} {:>COMMENTS_COLUMN_WIDTH$} {:>BLANKS_COLUMN_WIDTH$}",
                     l.name(),
                     e.name.to_string_lossy().to_string(),
@@ -441,11 +640,40 @@ impl Cli {
                     e.stats.comments,
                     e.stats.blanks
                 );
+                if let Some(mixed) = e.stats.mixed {
+                    print!(" {:>MIXED_COLUMN_WIDTH$}", mixed);
+                }
+                if let Some(logical) = e.stats.logical {
+                    print!(" {:>LOGICAL_COLUMN_WIDTH$}", logical);
+                }
+                println!();
             }),
             _ => None,
         };
 
-        config.types = self.types.take().or(config.types);
+        config.types = self
+            .types
+            .take()
+            .map(|types| types.iter().map(parse_or_exit::<LanguageType>).collect())
+            .or(config.types);
+
+        config.categories = self.categories.take().or(config.categories);
+        config.exclude_categories = self.exclude_categories.take().or(config.exclude_categories);
+
+        config.total_programming_only = if self.total_programming_only {
+            Some(true)
+        } else {
+            config.total_programming_only
+        };
+
+        config.group_by = self.group_by.or(config.group_by);
+        config.encoding = self.encoding.take().or(config.encoding);
+        config.mixed = if self.mixed { Some(true) } else { config.mixed };
+        config.logical_lines = if self.logical_lines {
+            Some(true)
+        } else {
+            config.logical_lines
+        };
 
         config
     }
@@ -11,11 +11,14 @@ use colored::Colorize;
 use num_format::ToFormattedString;
 
 use crate::input::Format;
-use tokei::{find_char_boundary, CodeStats, Language, LanguageType, Report};
+use tokei::{
+    find_char_boundary, CodeStats, Language, LanguageGroup, LanguageType, Report, SkipReason,
+    Skipped,
+};
 
 use crate::consts::{
-    BLANKS_COLUMN_WIDTH, CODE_COLUMN_WIDTH, COMMENTS_COLUMN_WIDTH, FILES_COLUMN_WIDTH,
-    LINES_COLUMN_WIDTH,
+    BLANKS_COLUMN_WIDTH, CODE_COLUMN_WIDTH, COMMENTS_COLUMN_WIDTH, FALLBACK_ROW_LEN,
+    FILES_COLUMN_WIDTH, LINES_COLUMN_WIDTH, LOGICAL_COLUMN_WIDTH, MIXED_COLUMN_WIDTH,
 };
 
 const NO_LANG_HEADER_ROW_LEN: usize = 69;
@@ -66,9 +69,10 @@ where
 }
 
 #[non_exhaustive]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone)]
 pub enum NumberFormatStyle {
     // 1234 (Default)
+    #[default]
     Plain,
     // 1,234
     Commas,
@@ -78,12 +82,6 @@ pub enum NumberFormatStyle {
     Underscores,
 }
 
-impl Default for NumberFormatStyle {
-    fn default() -> Self {
-        Self::Plain
-    }
-}
-
 impl FromStr for NumberFormatStyle {
     type Err = String;
 
@@ -126,23 +124,37 @@ pub struct Printer<W> {
     row: String,
     subrow: String,
     list_files: bool,
+    mixed: bool,
+    logical: bool,
     number_format: num_format::CustomFormat,
 }
 
 impl<W> Printer<W> {
+    /// Creates a printer for rows of `columns` width, with an extra Mixed
+    /// column when `mixed` is set, and a Logical column when `logical` is.
     pub fn new(
         columns: usize,
         list_files: bool,
+        mixed: bool,
+        logical: bool,
         writer: W,
         number_format: num_format::CustomFormat,
     ) -> Self {
+        let optional_width = usize::from(mixed) * (MIXED_COLUMN_WIDTH + 1)
+            + usize::from(logical) * (LOGICAL_COLUMN_WIDTH + 1);
+        // The other columns are never narrower than without the optional
+        // columns.
+        let columns = columns.saturating_sub(optional_width).max(FALLBACK_ROW_LEN);
+
         Self {
             columns,
             list_files,
+            mixed,
+            logical,
             path_length: columns - NO_LANG_ROW_LEN_NO_SPACES,
             writer,
-            row: "━".repeat(columns),
-            subrow: "─".repeat(columns),
+            row: "━".repeat(columns + optional_width),
+            subrow: "─".repeat(columns + optional_width),
             number_format,
         }
     }
@@ -153,7 +165,7 @@ impl<W: Write> Printer<W> {
         self.print_row()?;
 
         let files_column_width: usize = FILES_COLUMN_WIDTH + 6;
-        writeln!(
+        write!(
             self.writer,
             " {:<6$} {:>files_column_width$} {:>LINES_COLUMN_WIDTH$} {:>CODE_COLUMN_WIDTH$} {:>COMMENTS_COLUMN_WIDTH$} {:>BLANKS_COLUMN_WIDTH$}",
             "Language".bold().blue(),
@@ -164,6 +176,21 @@ impl<W: Write> Printer<W> {
             "Blanks".bold().blue(),
             self.columns - NO_LANG_HEADER_ROW_LEN
         )?;
+        if self.mixed {
+            write!(
+                self.writer,
+                " {:>MIXED_COLUMN_WIDTH$}",
+                "Mixed".bold().blue()
+            )?;
+        }
+        if self.logical {
+            write!(
+                self.writer,
+                " {:>LOGICAL_COLUMN_WIDTH$}",
+                "Logical".bold().blue()
+            )?;
+        }
+        writeln!(self.writer)?;
         self.print_row()
     }
 
@@ -181,7 +208,7 @@ impl<W: Write> Printer<W> {
     {
         self.print_language_name(language.inaccurate, name, None)?;
         write!(self.writer, " ")?;
-        writeln!(
+        write!(
             self.writer,
             "{:>FILES_COLUMN_WIDTH$} {:>LINES_COLUMN_WIDTH$} {:>CODE_COLUMN_WIDTH$} {:>COMMENTS_COLUMN_WIDTH$} {:>BLANKS_COLUMN_WIDTH$}",
             language
@@ -192,7 +219,8 @@ impl<W: Write> Printer<W> {
             language.code.to_formatted_string(&self.number_format),
             language.comments.to_formatted_string(&self.number_format),
             language.blanks.to_formatted_string(&self.number_format),
-        )
+        )?;
+        self.end_row(language.mixed, language.logical, false)
     }
 
     fn print_language_in_print_total(&mut self, language: &Language) -> io::Result<()>
@@ -201,7 +229,7 @@ impl<W: Write> Printer<W> {
     {
         self.print_language_name(language.inaccurate, "Total", None)?;
         write!(self.writer, " ")?;
-        writeln!(
+        write!(
             self.writer,
             "{:>FILES_COLUMN_WIDTH$} {:>LINES_COLUMN_WIDTH$} {:>CODE_COLUMN_WIDTH$} {:>COMMENTS_COLUMN_WIDTH$} {:>BLANKS_COLUMN_WIDTH$}",
             language
@@ -227,7 +255,8 @@ impl<W: Write> Printer<W> {
                 .blanks
                 .to_formatted_string(&self.number_format)
                 .blue(),
-        )
+        )?;
+        self.end_row(language.mixed, language.logical, true)
     }
 
     pub fn print_language_name(
@@ -272,17 +301,25 @@ impl<W: Write> Printer<W> {
         let mut code = 0;
         let mut comments = 0;
         let mut blanks = 0;
+        let mut mixed = None;
+        let mut logical = None;
 
         for stats in stats.iter().map(tokei::CodeStats::summarise) {
             code += stats.code;
             comments += stats.comments;
             blanks += stats.blanks;
+            if let Some(lines) = stats.mixed {
+                mixed = Some(mixed.unwrap_or(0) + lines);
+            }
+            if let Some(lines) = stats.logical {
+                logical = Some(logical.unwrap_or(0) + lines);
+            }
         }
 
         if stats.is_empty() {
             Ok(())
         } else {
-            writeln!(
+            write!(
                 self.writer,
                 " {:>FILES_COLUMN_WIDTH$} {:>LINES_COLUMN_WIDTH$} {:>CODE_COLUMN_WIDTH$} {:>COMMENTS_COLUMN_WIDTH$} {:>BLANKS_COLUMN_WIDTH$}",
                 stats.len().to_formatted_string(&self.number_format),
@@ -290,7 +327,8 @@ impl<W: Write> Printer<W> {
                 code.to_formatted_string(&self.number_format),
                 comments.to_formatted_string(&self.number_format),
                 blanks.to_formatted_string(&self.number_format),
-            )
+            )?;
+            self.end_row(mixed, logical, false)
         }
     }
 
@@ -309,6 +347,8 @@ impl<W: Write> Printer<W> {
         subtotal.stats.code += summary.code;
         subtotal.stats.comments += summary.comments;
         subtotal.stats.blanks += summary.blanks;
+        subtotal.stats.mixed = summary.mixed;
+        subtotal.stats.logical = summary.logical;
         self.print_report_with_name(&subtotal)?;
 
         Ok(())
@@ -350,7 +390,8 @@ impl<W: Write> Printer<W> {
                     }
                     if compact {
                         for &report in &reports {
-                            writeln!(self.writer, "{:1$}", report, self.path_length)?;
+                            write!(self.writer, "{:1$}", report, self.path_length)?;
+                            self.end_row(report.stats.mixed, report.stats.logical, false)?;
                         }
                     } else {
                         let (a, b): (Vec<&Report>, Vec<&Report>) =
@@ -359,7 +400,8 @@ impl<W: Write> Printer<W> {
                             let mut first = true;
                             for report in reports.iter() {
                                 if report.stats.blobs.is_empty() {
-                                    writeln!(self.writer, "{:1$}", report, self.path_length)?;
+                                    write!(self.writer, "{:1$}", report, self.path_length)?;
+                                    self.end_row(report.stats.mixed, report.stats.logical, false)?;
                                 } else {
                                     if first && a.is_empty() {
                                         writeln!(self.writer, " {}", report.name.display())?;
@@ -370,7 +412,7 @@ impl<W: Write> Printer<W> {
                                             "-- {} {}",
                                             report.name.display(),
                                             "-".repeat(
-                                                self.columns
+                                                self.width()
                                                     - 4
                                                     - report.name.display().to_string().len()
                                             )
@@ -378,12 +420,17 @@ impl<W: Write> Printer<W> {
                                     }
                                     let mut new_report = (*report).clone();
                                     new_report.name = name.to_string().into();
-                                    writeln!(
+                                    write!(
                                         self.writer,
                                         " |-{:1$}",
                                         new_report,
                                         self.path_length - 3
                                     )?;
+                                    self.end_row(
+                                        new_report.stats.mixed,
+                                        new_report.stats.logical,
+                                        false,
+                                    )?;
                                     self.print_report_total(report, language.inaccurate)?;
                                 }
                             }
@@ -396,6 +443,123 @@ impl<W: Write> Printer<W> {
         Ok(())
     }
 
+    fn print_group(&mut self, group: &LanguageGroup, name: &str) -> io::Result<()> {
+        self.print_language_name(group.inaccurate, name, None)?;
+        write!(
+            self.writer,
+            " {:>FILES_COLUMN_WIDTH$} {:>LINES_COLUMN_WIDTH$} {:>CODE_COLUMN_WIDTH$} {:>COMMENTS_COLUMN_WIDTH$} {:>BLANKS_COLUMN_WIDTH$}",
+            group.files().to_formatted_string(&self.number_format),
+            group.lines().to_formatted_string(&self.number_format),
+            group.code.to_formatted_string(&self.number_format),
+            group.comments.to_formatted_string(&self.number_format),
+            group.blanks.to_formatted_string(&self.number_format),
+        )?;
+        self.end_row(group.mixed, group.logical, false)
+    }
+
+    fn print_group_member(
+        &mut self,
+        language_type: LanguageType,
+        language: &Language,
+    ) -> io::Result<()> {
+        let summary = language.summarise();
+        self.print_language_name(summary.inaccurate, language_type.name(), Some(" |-"))?;
+        write!(
+            self.writer,
+            " {:>FILES_COLUMN_WIDTH$} {:>LINES_COLUMN_WIDTH$} {:>CODE_COLUMN_WIDTH$} {:>COMMENTS_COLUMN_WIDTH$} {:>BLANKS_COLUMN_WIDTH$}",
+            summary.reports.len().to_formatted_string(&self.number_format),
+            summary.lines().to_formatted_string(&self.number_format),
+            summary.code.to_formatted_string(&self.number_format),
+            summary.comments.to_formatted_string(&self.number_format),
+            summary.blanks.to_formatted_string(&self.number_format),
+        )?;
+        self.end_row(summary.mixed, summary.logical, false)
+    }
+
+    /// Prints a row for each group of languages, followed by a breakdown of
+    /// the languages in the group, unless `compact` is set or the group is a
+    /// single language.
+    pub fn print_groups<'a, I>(
+        &mut self,
+        groups: I,
+        compact: bool,
+        is_sorted: bool,
+    ) -> io::Result<()>
+    where
+        I: Iterator<Item = (&'a String, &'a LanguageGroup)>,
+    {
+        let mut first = true;
+        let mut had_members = false;
+
+        for (name, group) in groups.filter(|(_, g)| g.lines() != 0 || g.files() != 0) {
+            let has_members = !compact && group.languages.len() > 1;
+            if first {
+                first = false;
+            } else if has_members || had_members || self.list_files {
+                self.print_subrow()?;
+            }
+            had_members = has_members;
+
+            self.print_group(group, name)?;
+            if has_members {
+                for (language_type, language) in &group.languages {
+                    self.print_group_member(*language_type, language)?;
+                }
+            }
+
+            if self.list_files {
+                self.print_subrow()?;
+                let mut reports: Vec<&Report> = group
+                    .languages
+                    .values()
+                    .flat_map(|language| &language.reports)
+                    .collect();
+                if !is_sorted {
+                    reports.sort_by(|&a, &b| a.name.cmp(&b.name));
+                }
+                for report in reports {
+                    write!(self.writer, "{:1$}", report, self.path_length)?;
+                    self.end_row(report.stats.mixed, report.stats.logical, false)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The width of a row, including the optional columns that are enabled.
+    fn width(&self) -> usize {
+        self.row.chars().count()
+    }
+
+    /// Ends a row of statistics, with the Mixed and Logical columns when
+    /// they're enabled.
+    /// Ends a row with the optional Mixed and Logical columns, in the colour
+    /// of the totals if `highlight` is set.
+    fn end_row(
+        &mut self,
+        mixed: Option<usize>,
+        logical: Option<usize>,
+        highlight: bool,
+    ) -> io::Result<()> {
+        let columns = [
+            (self.mixed, mixed, MIXED_COLUMN_WIDTH),
+            (self.logical, logical, LOGICAL_COLUMN_WIDTH),
+        ];
+        for (enabled, count, width) in columns {
+            if !enabled {
+                continue;
+            }
+            let count = count.unwrap_or(0).to_formatted_string(&self.number_format);
+            if highlight {
+                write!(self.writer, " {:>width$}", count.blue())?;
+            } else {
+                write!(self.writer, " {:>width$}", count)?;
+            }
+        }
+        writeln!(self.writer)
+    }
+
     fn print_row(&mut self) -> io::Result<()> {
         writeln!(self.writer, "{}", self.row)
     }
@@ -412,7 +576,7 @@ impl<W: Write> Printer<W> {
     ) -> io::Result<()> {
         self.print_language_name(inaccurate, &language_type.to_string(), Some(" |-"))?;
 
-        writeln!(
+        write!(
             self.writer,
             " {:>FILES_COLUMN_WIDTH$} {:>LINES_COLUMN_WIDTH$} {:>CODE_COLUMN_WIDTH$} {:>COMMENTS_COLUMN_WIDTH$} {:>BLANKS_COLUMN_WIDTH$}",
             " ",
@@ -420,7 +584,8 @@ impl<W: Write> Printer<W> {
             stats.code.to_formatted_string(&self.number_format),
             stats.comments.to_formatted_string(&self.number_format),
             stats.blanks.to_formatted_string(&self.number_format),
-        )
+        )?;
+        self.end_row(stats.mixed, stats.logical, false)
     }
 
     fn print_report_total(&mut self, report: &Report, inaccurate: bool) -> io::Result<()> {
@@ -465,7 +630,7 @@ impl<W: Write> Printer<W> {
         report: &Report,
     ) -> io::Result<()> {
         let lines_column_width: usize = FILES_COLUMN_WIDTH + 6;
-        writeln!(
+        write!(
             self.writer,
             " {: <max$} {:>lines_column_width$} {:>CODE_COLUMN_WIDTH$} {:>COMMENTS_COLUMN_WIDTH$} {:>BLANKS_COLUMN_WIDTH$}",
             name,
@@ -480,11 +645,44 @@ impl<W: Write> Printer<W> {
                 .to_formatted_string(&self.number_format),
             report.stats.blanks.to_formatted_string(&self.number_format),
             max = max_len
-        )
+        )?;
+        self.end_row(report.stats.mixed, report.stats.logical, false)
     }
 
-    pub fn print_total(&mut self, languages: &tokei::Languages) -> io::Result<()> {
-        let total = languages.total();
+    /// Prints the files that were skipped without being counted, e.g.
+    /// `skipped: binary ./video.ts`. Unless files are listed, vendored,
+    /// generated, and documentation files are only counted, as there can be
+    /// thousands of them.
+    pub fn print_skipped(&mut self, skipped: &[Skipped]) -> io::Result<()> {
+        let mut excluded = std::collections::BTreeMap::<SkipReason, usize>::new();
+        for skipped in skipped {
+            if self.list_files || skipped.reason == SkipReason::Binary {
+                writeln!(self.writer, "{}", skipped.to_string().dimmed())?;
+            } else {
+                *excluded.entry(skipped.reason).or_default() += 1;
+            }
+        }
+
+        for (reason, files) in excluded {
+            let line = format!(
+                "skipped: {} {} file{}, count them with --{}",
+                files.to_formatted_string(&self.number_format),
+                reason,
+                if files == 1 { "" } else { "s" },
+                reason,
+            );
+            writeln!(self.writer, "{}", line.dimmed())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn print_total(
+        &mut self,
+        languages: &tokei::Languages,
+        config: &tokei::Config,
+    ) -> io::Result<()> {
+        let total = languages.total_for(config);
         self.print_row()?;
         self.print_language_in_print_total(&total)?;
         self.print_row()
@@ -1,10 +1,21 @@
-use std::{env, fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    slice,
+};
 
+use encoding_rs::Encoding;
 use etcetera::BaseStrategy;
+use globset::GlobSet;
+use once_cell::sync::OnceCell;
 
-use crate::language::LanguageType;
+use crate::category::Category;
+use crate::group_by::GroupBy;
+use crate::language::{custom, LanguageType};
 use crate::sort::Sort;
-use crate::stats::Report;
+use crate::stats::{FileKind, Report};
+use crate::utils::fs as fsutils;
 
 /// A configuration struct for how [`Languages::get_statistics`] searches and
 /// counts languages.
@@ -18,6 +29,10 @@ use crate::stats::Report;
 /// };
 /// ```
 ///
+/// The globs of `languages` and `encodings` are compiled the first time a file
+/// is matched against them, so changes to those options after a `Config` has
+/// been used aren't seen.
+///
 /// [`Languages::get_statistics`]: struct.Languages.html#method.get_statistics
 #[derive(Debug, Default, Deserialize)]
 pub struct Config {
@@ -38,22 +53,116 @@ pub struct Config {
     /// Don't respect VCS ignore files (.gitignore, .hgignore, etc.), including those in
     /// parent directories. *Default:* `false`.
     pub no_ignore_vcs: Option<bool>,
+    /// Count vendored files, such as those in `vendor` and `node_modules`
+    /// directories. *Default:* `false`.
+    pub vendored: Option<bool>,
+    /// Count generated files, such as minified JavaScript and files marked
+    /// `DO NOT EDIT`. *Default:* `false`.
+    pub generated: Option<bool>,
+    /// Count documentation, marked with `linguist-documentation` in
+    /// `.gitattributes`. *Default:* `false`.
+    pub documentation: Option<bool>,
+    /// The encoding of files without a byte order mark, as a label such as
+    /// `Shift_JIS`, `GBK`, or `windows-1252`. *Default:* UTF-8, or UTF-16 when
+    /// detected.
+    pub encoding: Option<String>,
+    /// Encodings of the files matching globs, keyed by the glob. Takes
+    /// precedence over `encoding`, and the longest matching glob is used.
+    /// *Default:* `None`.
+    pub encodings: Option<BTreeMap<String, String>>,
     /// Whether to treat doc strings in languages as comments.  *Default:*
     /// `false`.
     pub treat_doc_strings_as_comments: Option<bool>,
+    /// Count lines of code that also contain a comment, e.g.
+    /// `let x = 1; // one`, in `CodeStats.mixed`. These lines are still
+    /// counted as code. *Default:* `false`.
+    pub mixed: Option<bool>,
+    /// Count the logical lines, i.e. statements, of languages with a
+    /// `logical_lines` syntax in `CodeStats.logical`. *Default:* `false`.
+    pub logical_lines: Option<bool>,
     /// Sort languages. *Default:* `None`.
     pub sort: Option<Sort>,
     /// Filters languages searched to just those provided. E.g. A directory
     /// containing `C`, `Cpp`, and `Rust` with a `Config.types` of `[Cpp, Rust]`
     /// will count only `Cpp` and `Rust`. *Default:* `None`.
     pub types: Option<Vec<LanguageType>>,
-    // /// A map of individual language configuration.
-    // pub languages: Option<HashMap<LanguageType, LanguageConfig>>,
+    /// Filters languages searched to just those in the provided categories.
+    /// E.g. a `Config.categories` of `[programming]` will skip `JSON` and
+    /// `Markdown`. *Default:* `None`.
+    pub categories: Option<Vec<Category>>,
+    /// Skips languages in the provided categories. Takes precedence over
+    /// `categories` and `types`. *Default:* `None`.
+    pub exclude_categories: Option<Vec<Category>>,
+    /// Leave languages that aren't in the `programming` category out of
+    /// [`Languages::total_for`], while still counting and reporting them.
+    /// *Default:* `false`.
+    ///
+    /// [`Languages::total_for`]: struct.Languages.html#method.total_for
+    pub total_programming_only: Option<bool>,
+    /// Groups of languages counted as a single row with `GroupBy::Group`,
+    /// keyed by the name of the group. Languages in one of these groups are
+    /// taken out of their group from `languages.json`. *Default:* `None`.
+    pub groups: Option<BTreeMap<String, Vec<LanguageType>>>,
+    /// What each row of the output counts. _This option is ignored in the
+    /// library, see [`Languages::groups`]._ *Default:* `GroupBy::Language`.
+    ///
+    /// [`Languages::groups`]: struct.Languages.html#method.groups
+    pub group_by: Option<GroupBy>,
+    /// A map of individual language configuration, keyed by the name of the
+    /// language. Names of tokei's languages remap files to that language,
+    /// other names define a new language. *Default:* `None`.
+    pub languages: Option<BTreeMap<String, LanguageConfig>>,
     /// Whether to output only the paths for downstream batch processing
     /// *Default:* false
     #[serde(skip)]
     /// Adds a closure for each function, e.g., print the result
     pub for_each_fn: Option<fn(LanguageType, Report)>,
+    /// The options above that are matched against every file, compiled the
+    /// first time they're used.
+    #[serde(skip)]
+    #[doc(hidden)]
+    pub compiled: Compiled,
+}
+
+/// The parts of a [`Config`] that are worked out once, rather than for every
+/// file. Options changed after the first file is counted aren't seen.
+#[derive(Debug, Default)]
+pub struct Compiled(OnceCell<CompiledOptions>);
+
+#[derive(Debug)]
+struct CompiledOptions {
+    /// The language of each of `Config.languages`, in the same order, along
+    /// with its `paths`.
+    languages: Vec<(LanguageType, GlobSet)>,
+    /// The globs of `Config.encodings`, along with the length of the glob and
+    /// the label of the encoding.
+    encodings: Vec<(GlobSet, usize, String)>,
+}
+
+impl CompiledOptions {
+    fn new(config: &Config) -> Self {
+        Self {
+            languages: config
+                .languages
+                .iter()
+                .flatten()
+                .map(|(name, language)| {
+                    let language_type = LanguageType::from_builtin_name(name)
+                        .unwrap_or_else(|| custom::register(name, language));
+                    (language_type, fsutils::glob_set(&language.paths))
+                })
+                .collect(),
+            encodings: config
+                .encodings
+                .iter()
+                .flatten()
+                .map(|(pattern, label)| {
+                    let glob = fsutils::glob_set(slice::from_ref(pattern));
+                    (glob, pattern.len(), label.clone())
+                })
+                .collect(),
+        }
+    }
 }
 
 impl Config {
@@ -62,10 +171,35 @@ impl Config {
     /// as the latter is a hidden file on Unix and not an idiomatic
     /// filename on Windows.
     fn get_config(base: PathBuf) -> Option<Self> {
-        fs::read_to_string(base.join("tokei.toml"))
+        /// Just the `languages` table of a configuration file.
+        #[derive(Deserialize)]
+        struct Languages {
+            languages: Option<BTreeMap<String, LanguageConfig>>,
+        }
+
+        let (path, contents) = ["tokei.toml", ".tokeirc"].iter().find_map(|name| {
+            let path = base.join(name);
+            fs::read_to_string(&path)
+                .ok()
+                .map(|contents| (path, contents))
+        })?;
+
+        // Custom languages have to be registered before the rest of the file
+        // is deserialized, so that options such as `types` can refer to them.
+        if let Ok(Languages {
+            languages: Some(languages),
+        }) = toml::from_str(&contents)
+        {
+            for (name, language) in &languages {
+                if LanguageType::from_builtin_name(name).is_none() {
+                    custom::register(name, language);
+                }
+            }
+        }
+
+        toml::from_str(&contents)
+            .map_err(|error| error!("Couldn't parse {}:\n{}", path.display(), error))
             .ok()
-            .or_else(|| fs::read_to_string(base.join(".tokeirc")).ok())
-            .and_then(|s| toml::from_str(&s).ok())
     }
 
     /// Creates a `Config` from three configuration files if they are available.
@@ -86,10 +220,17 @@ impl Config {
     /// ```toml
     /// columns = 80
     /// types = ["Python"]
+    /// exclude_categories = ["data"]
     /// treat_doc_strings_as_comments = true
-    // ///
-    // /// [[languages.Python]]
-    // /// extensions = ["py3"]
+    ///
+    /// [languages.Acme]
+    /// line_comment = ["#"]
+    /// quotes = [["\"", "\""]]
+    /// extensions = ["acme"]
+    ///
+    /// [languages.Cpp]
+    /// extensions = ["h"]
+    /// paths = ["src/cpp/**"]
     /// ```
     pub fn from_config_files() -> Self {
         let conf_dir = etcetera::choose_base_strategy()
@@ -109,17 +250,51 @@ impl Config {
             .unwrap_or_default();
 
         #[allow(clippy::or_fun_call)]
-        Config {
+        let config = Config {
             columns: current_dir
                 .columns
                 .or(home_dir.columns.or(conf_dir.columns)),
             hidden: current_dir.hidden.or(home_dir.hidden.or(conf_dir.hidden)),
-            //languages: current_dir.languages.or(conf_dir.languages),
+            vendored: current_dir
+                .vendored
+                .or(home_dir.vendored.or(conf_dir.vendored)),
+            generated: current_dir
+                .generated
+                .or(home_dir.generated.or(conf_dir.generated)),
+            documentation: current_dir
+                .documentation
+                .or(home_dir.documentation.or(conf_dir.documentation)),
+            languages: current_dir
+                .languages
+                .or(home_dir.languages.or(conf_dir.languages)),
             treat_doc_strings_as_comments: current_dir.treat_doc_strings_as_comments.or(home_dir
                 .treat_doc_strings_as_comments
                 .or(conf_dir.treat_doc_strings_as_comments)),
+            mixed: current_dir.mixed.or(home_dir.mixed.or(conf_dir.mixed)),
+            logical_lines: current_dir
+                .logical_lines
+                .or(home_dir.logical_lines.or(conf_dir.logical_lines)),
             sort: current_dir.sort.or(home_dir.sort.or(conf_dir.sort)),
             types: current_dir.types.or(home_dir.types.or(conf_dir.types)),
+            encoding: current_dir
+                .encoding
+                .or(home_dir.encoding.or(conf_dir.encoding)),
+            encodings: current_dir
+                .encodings
+                .or(home_dir.encodings.or(conf_dir.encodings)),
+            groups: current_dir.groups.or(home_dir.groups.or(conf_dir.groups)),
+            group_by: current_dir
+                .group_by
+                .or(home_dir.group_by.or(conf_dir.group_by)),
+            categories: current_dir
+                .categories
+                .or(home_dir.categories.or(conf_dir.categories)),
+            exclude_categories: current_dir
+                .exclude_categories
+                .or(home_dir.exclude_categories.or(conf_dir.exclude_categories)),
+            total_programming_only: current_dir.total_programming_only.or(home_dir
+                .total_programming_only
+                .or(conf_dir.total_programming_only)),
             for_each_fn: current_dir
                 .for_each_fn
                 .or(home_dir.for_each_fn.or(conf_dir.for_each_fn)),
@@ -135,54 +310,254 @@ impl Config {
             no_ignore_vcs: current_dir
                 .no_ignore_vcs
                 .or(home_dir.no_ignore_vcs.or(conf_dir.no_ignore_vcs)),
+            compiled: Compiled::default(),
+        };
+
+        // Resolve the languages of the `languages` table up front, rather
+        // than when the first file is matched against them.
+        config.compiled();
+        config
+    }
+
+    fn compiled(&self) -> &CompiledOptions {
+        self.compiled.0.get_or_init(|| CompiledOptions::new(self))
+    }
+
+    /// Whether files of `language` are counted, according to `types`,
+    /// `categories`, and `exclude_categories`.
+    pub(crate) fn is_counted(&self, language: LanguageType) -> bool {
+        let category = language.category();
+
+        self.types
+            .as_ref()
+            .map_or(true, |types| types.contains(&language))
+            && self
+                .categories
+                .as_ref()
+                .map_or(true, |categories| categories.contains(&category))
+            && !self
+                .exclude_categories
+                .as_ref()
+                .is_some_and(|categories| categories.contains(&category))
+    }
+
+    /// Whether files of `kind` are counted, according to `vendored`,
+    /// `generated`, and `documentation`. Code written by hand is always
+    /// counted.
+    pub(crate) fn is_kind_counted(&self, kind: Option<FileKind>) -> bool {
+        match kind {
+            None => true,
+            Some(FileKind::Vendored) => self.vendored.unwrap_or(false),
+            Some(FileKind::Generated) => self.generated.unwrap_or(false),
+            Some(FileKind::Documentation) => self.documentation.unwrap_or(false),
         }
     }
+
+    /// Get the encoding of the file at `path` from `encodings` or `encoding`.
+    /// Unknown labels are ignored.
+    pub(crate) fn encoding_for(&self, path: &Path) -> Option<&'static Encoding> {
+        let label = self
+            .compiled()
+            .encodings
+            .iter()
+            .filter(|(glob, _, _)| glob.is_match(path))
+            .max_by_key(|(_, length, _)| *length)
+            .map(|(_, _, label)| label)
+            .or(self.encoding.as_ref())?;
+
+        Encoding::for_label(label.as_bytes()).or_else(|| {
+            warn!("Unknown encoding {:?} for {}", label, path.display());
+            None
+        })
+    }
+
+    /// Get the name of the group that `language` is counted in, from `groups`
+    /// or `languages.json`.
+    pub(crate) fn group_of(&self, language: LanguageType) -> Option<&str> {
+        self.groups
+            .iter()
+            .flatten()
+            .find(|(_, languages)| languages.contains(&language))
+            .map(|(name, _)| name.as_str())
+            .or_else(|| language.group())
+    }
+
+    /// Get the language that `languages` assigns to a file path by its file
+    /// name or extension. Entries restricted to `paths` take precedence over
+    /// the ones that apply everywhere.
+    pub(crate) fn language_from_path(&self, path: &Path) -> Option<LanguageType> {
+        let languages = self.languages.as_ref()?;
+        let filename = fsutils::get_filename(path);
+        let extension = fsutils::get_extension(path);
+
+        let entries = || languages.values().zip(&self.compiled().languages);
+        let restricted = entries().filter(|(language, _)| !language.paths.is_empty());
+        let unrestricted = entries().filter(|(language, _)| language.paths.is_empty());
+
+        restricted
+            .chain(unrestricted)
+            .find(|(language, (_, paths))| {
+                language.matches_name(filename.as_deref(), extension.as_deref())
+                    && (language.paths.is_empty() || paths.is_match(path))
+            })
+            .map(|(_, (language_type, _))| *language_type)
+    }
 }
 
-/*
-/// Configuration for an individual [`LanguageType`].
+/// Configuration for an individual language, read from the `[languages]`
+/// table of the configuration file.
+///
+/// When keyed by the name of one of tokei's languages, the `extensions` and
+/// `filenames` are remapped to that language, optionally only within `paths`,
+/// and the syntax properties are ignored. Any other name defines a new
+/// language, described with the same properties as tokei's own
+/// `languages.json`. Custom languages are counted and reported alongside the
+/// built-in ones as [`LanguageType::Custom`].
 ///
 /// ```
-/// use std::collections::HashMap;
-/// use tokei::{Config, LanguageConfig, LanguageType};
+/// use std::collections::BTreeMap;
+/// use tokei::{Config, LanguageConfig};
 ///
 /// let config = Config {
 ///     languages: {
-///         let cpp_conf = LanguageConfig {
-///             extensions: vec![String::from("c")],
-///         };
+///         let mut acme = LanguageConfig::new();
+///         acme.extensions(vec![String::from("acme")]);
+///         acme.line_comment = vec![String::from("#")];
+///
+///         // Headers in `src/cpp` are C++, everywhere else they're C headers.
+///         let mut cpp = LanguageConfig::new();
+///         cpp.extensions(vec![String::from("h")]);
+///         cpp.paths(vec![String::from("src/cpp/**")]);
 ///
-///         let mut languages_config = HashMap::new();
-///         languages_config.insert(LanguageType::Cpp, cpp_conf);
+///         let mut languages_config = BTreeMap::new();
+///         languages_config.insert(String::from("Acme"), acme);
+///         languages_config.insert(String::from("Cpp"), cpp);
 ///
 ///         Some(languages_config)
 ///     },
 ///
 ///     ..Config::default()
 /// };
-///
 /// ```
 ///
-/// [`LanguageType`]: enum.LanguageType.html
-#[derive(Debug, Default, Deserialize)]
+/// [`LanguageType::Custom`]: enum.LanguageType.html#variant.Custom
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
 pub struct LanguageConfig {
-    /// Additional extensions for a language. Any extensions that overlap with
-    /// already defined extensions from `tokei` will be ignored.
+    /// Single line comments, e.g. `//`.
+    pub line_comment: Vec<String>,
+    /// Single line doc comments, e.g. `///`, which must start with one of
+    /// `line_comment`.
+    pub doc_line_comment: Vec<String>,
+    /// Keywords of top-level declarations, e.g. Go's `func`, whose directly
+    /// preceding line comments are counted as docs.
+    pub doc_declarations: Vec<String>,
+    /// Pairs of starting and ending multi line comments, e.g. `/*` and `*/`.
+    pub multi_line_comments: Vec<(String, String)>,
+    /// Pairs of starting and ending multi line doc comments, e.g. `/**` and
+    /// `*/`, which must start with one of `multi_line_comments`.
+    pub doc_multi_line_comments: Vec<(String, String)>,
+    /// The syntax of statements, used to count logical lines.
+    pub logical_lines: Option<LogicalLinesConfig>,
+    /// Whether multi line comments can be nested.
+    pub nested: bool,
+    /// Pairs of multi line comments that can always be nested, regardless of
+    /// `nested`.
+    pub nested_comments: Vec<(String, String)>,
+    /// Templates of multi line comments whose end repeats part of their
+    /// start, like `raw_quotes`, e.g. `--[{=}[` and `]{=}]` for `--[==[…]==]`.
+    pub raw_comments: Vec<(String, String)>,
+    /// Pairs of starting and ending string literal quotes.
+    pub quotes: Vec<(String, String)>,
+    /// Pairs of quotes for string literals that can't contain escapes.
+    pub verbatim_quotes: Vec<(String, String)>,
+    /// Templates of quotes for string literals that can't contain escapes,
+    /// and whose closing quote repeats part of the opening one. A `{c}`
+    /// placeholder matches any number of the character `c`, e.g. `r{#}"` and
+    /// `"{#}` for `r#"…"#`, and `{}` any short delimiter, e.g. `R"{}(` and
+    /// `){}"` for `R"x(…)x"`. Templates that `languages.json` can't have,
+    /// e.g. without the same placeholder in both quotes, are ignored with a
+    /// warning.
+    pub raw_quotes: Vec<(String, String)>,
+    /// Interpolations within strings, as the start of the string along with
+    /// the start and end of the interpolation, e.g. `` ` ``, `${` and `}`.
+    pub interpolations: Vec<(String, String, String)>,
+    /// Operators that open a heredoc, e.g. `<<`, which are followed by a tag,
+    /// e.g. `EOF`, and a body on the following lines until a line starting
+    /// with the tag.
+    pub heredocs: Vec<String>,
+    /// Pairs of quotes for doc strings, which can be counted as comments.
+    pub doc_quotes: Vec<(String, String)>,
+    /// Whether the language is primarily documentation, and so is counted as
+    /// comments.
+    pub literate: bool,
+    /// The category of the language. *Default:* `programming`.
+    pub category: Option<Category>,
+    /// File extensions of the language.
     pub extensions: Vec<String>,
+    /// Whole file names of the language, e.g. `makefile`.
+    pub filenames: Vec<String>,
+    /// Shebang lines of the language, e.g. `#!/bin/bash`.
+    pub shebangs: Vec<String>,
+    /// Programs used in an `#!/usr/bin/env` shebang, e.g. `bash`.
+    pub env: Vec<String>,
+    /// Globs restricting `extensions` and `filenames` to matching paths, e.g.
+    /// `src/cpp/**`. Patterns that don't start with `/` or `**` can match at
+    /// any depth. Applies everywhere when empty.
+    pub paths: Vec<String>,
+}
+
+/// The syntax of a language's statements, used to count its logical lines
+/// with `Config.logical_lines`, see [`LanguageConfig`].
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LogicalLinesConfig {
+    /// Tokens that end a statement, e.g. `;`. Each line of code is a statement
+    /// when there are none, such as in shell scripts.
+    pub terminators: Vec<String>,
+    /// Tokens that open a block, e.g. `{`, each counted as a statement.
+    pub blocks: Vec<String>,
+    /// Prefixes of lines that are a single statement, e.g. `#` for the
+    /// directives of the C preprocessor.
+    pub directives: Vec<String>,
+    /// The end of a line that continues the statement onto the next line,
+    /// e.g. `\`.
+    pub continuation: Option<String>,
 }
 
 impl LanguageConfig {
     /// Creates a new empty configuration. By default this will not change
     /// anything from the default.
+    #[must_use]
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Accepts a `Vec<String>` representing additional extensions for a
-    /// language. Any extensions that overlap with already defined extensions
-    /// from `tokei` will be ignored.
+    /// Accepts a `Vec<String>` representing the extensions of the language.
     pub fn extensions(&mut self, extensions: Vec<String>) {
         self.extensions = extensions;
     }
+
+    /// Accepts a `Vec<String>` representing the file names of the language.
+    pub fn filenames(&mut self, filenames: Vec<String>) {
+        self.filenames = filenames;
+    }
+
+    /// Accepts a `Vec<String>` of globs restricting where the extensions and
+    /// file names apply.
+    pub fn paths(&mut self, paths: Vec<String>) {
+        self.paths = paths;
+    }
+
+    fn matches_name(&self, filename: Option<&str>, extension: Option<&str>) -> bool {
+        filename.is_some_and(|filename| {
+            self.filenames
+                .iter()
+                .any(|f| f.eq_ignore_ascii_case(filename))
+        }) || extension.is_some_and(|extension| {
+            self.extensions
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(extension))
+        })
+    }
 }
-*/
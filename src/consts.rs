@@ -25,3 +25,9 @@ pub const COMMENTS_COLUMN_WIDTH: usize = 12;
 
 /// Blanks column width
 pub const BLANKS_COLUMN_WIDTH: usize = 12;
+
+/// Mixed column width
+pub const MIXED_COLUMN_WIDTH: usize = 12;
+
+/// Logical column width
+pub const LOGICAL_COLUMN_WIDTH: usize = 12;
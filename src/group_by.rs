@@ -0,0 +1,39 @@
+use std::str::FromStr;
+
+use serde::de::{self, Deserialize, Deserializer};
+
+/// Used for choosing what each row of the output counts.
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub enum GroupBy {
+    /// A row for each language.
+    #[default]
+    Language,
+    /// A row for each group of languages, from `languages.json` or
+    /// `Config.groups`, and for each language that isn't in a group.
+    Group,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if s.eq_ignore_ascii_case("language") {
+            GroupBy::Language
+        } else if s.eq_ignore_ascii_case("group") {
+            GroupBy::Group
+        } else {
+            return Err(format!("Unsupported grouping option: {}", s));
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for GroupBy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
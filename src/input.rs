@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, error::Error, str::FromStr};
 
-use tokei::{Language, LanguageType, Languages};
+use tokei::{Config, Language, LanguageGroup, LanguageSyntax, LanguageType, Languages, Skipped};
 
 type LanguageMap = BTreeMap<LanguageType, Language>;
 
@@ -11,6 +11,42 @@ struct Output {
     languages: LanguageMap,
     #[serde(rename = "Total")]
     totals: Language,
+    #[serde(rename = "Skipped", default, skip_serializing_if = "Vec::is_empty")]
+    skipped: Vec<Skipped>,
+}
+
+/// The output with `--group-by group`, where each group has a breakdown of
+/// its languages.
+#[derive(Deserialize, Serialize, Debug)]
+struct GroupedOutput {
+    #[serde(flatten)]
+    groups: BTreeMap<String, LanguageGroup>,
+    #[serde(rename = "Total")]
+    totals: Language,
+    #[serde(rename = "Skipped", default, skip_serializing_if = "Vec::is_empty")]
+    skipped: Vec<Skipped>,
+}
+
+/// Either kind of output, so that grouped output can be read back in with
+/// `--input`.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Input {
+    Languages(Output),
+    Groups(GroupedOutput),
+}
+
+impl Input {
+    fn into_languages(self) -> LanguageMap {
+        match self {
+            Input::Languages(output) => output.languages,
+            Input::Groups(output) => output
+                .groups
+                .into_values()
+                .flat_map(|group| group.languages)
+                .collect(),
+        }
+    }
 }
 
 macro_rules! supported_formats {
@@ -48,12 +84,6 @@ macro_rules! supported_formats {
                 ]
             }
 
-            pub fn all() -> &'static [&'static str] {
-                &[
-                    $( stringify!($name) ),+
-                ]
-            }
-
             pub fn all_feature_names() -> &'static [&'static str] {
                 &[
                     $( $feature ),+
@@ -73,8 +103,8 @@ macro_rules! supported_formats {
                     return None
                 }
 
-                if let Ok(Output { languages, .. }) = serde_json::from_str::<Output>(input) {
-                    return Some(languages);
+                if let Ok(input) = serde_json::from_str::<Input>(input) {
+                    return Some(input.into_languages());
                 }
 
                 $(
@@ -83,8 +113,8 @@ macro_rules! supported_formats {
                     {
                         let parse = &{ $parse_kode };
 
-                        if let Ok(Output { languages, .. }) = parse(input) {
-                            return Some(languages)
+                        if let Ok(input) = parse(input) {
+                            return Some(Input::into_languages(input))
                         }
                     }
                 )+
@@ -93,18 +123,53 @@ macro_rules! supported_formats {
                 None
             }
 
-            pub fn print(&self, languages: &Languages) -> Result<String, Box<dyn Error>> {
+            pub fn print(
+                &self,
+                languages: &Languages,
+                config: &Config,
+            ) -> Result<String, Box<dyn Error>> {
                 let output = Output {
                     languages: (*languages).to_owned(),
-                    totals: languages.total()
+                    totals: languages.total_for(config),
+                    skipped: languages.skipped().to_vec(),
                 };
 
+                self.serialize(&output)
+            }
+
+            /// Prints the languages folded into their groups, see
+            /// `Languages::groups`.
+            pub fn print_groups(
+                &self,
+                languages: &Languages,
+                config: &Config,
+            ) -> Result<String, Box<dyn Error>> {
+                let output = GroupedOutput {
+                    groups: languages.groups(config),
+                    totals: languages.total_for(config),
+                    skipped: languages.skipped().to_vec(),
+                };
+
+                self.serialize(&output)
+            }
+
+            /// Prints the syntax of every supported language, keyed by name.
+            pub fn print_languages(&self) -> Result<String, Box<dyn Error>> {
+                let languages: BTreeMap<LanguageType, LanguageSyntax> = LanguageType::list()
+                    .iter()
+                    .map(|&(language, _)| (language, language.syntax()))
+                    .collect();
+
+                self.serialize(&languages)
+            }
+
+            fn serialize<T: Serialize>(&self, value: &T) -> Result<String, Box<dyn Error>> {
                 match *self {
-                    Format::Json => Ok(serde_json::to_string(&output)?),
+                    Format::Json => Ok(serde_json::to_string(value)?),
                     $(
                         #[cfg(feature = $feature)] Format::$variant => {
                             let print= &{ $print_kode };
-                            Ok(print(&output)?)
+                            Ok(print(value)?)
                         }
                     ),+
                 }
@@ -155,10 +220,6 @@ supported_formats!(
         },
         |languages| serde_cbor::to_vec(&languages).map(hex::encode),
 
-    (json, "json", Json [serde_json]) =>
-        serde_json::from_str,
-        serde_json::to_string,
-
     (yaml, "yaml", Yaml [serde_yaml]) =>
         serde_yaml::from_str,
         serde_yaml::to_string,
@@ -218,16 +279,60 @@ mod tests {
         // Get language results from sample dir
         let data_dir = Path::new("tests").join("data");
         let mut langs = Languages::new();
-        langs.get_statistics(&[data_dir], &[], &Config::default());
+        // The files in `tests/data` are marked with `linguist-documentation`.
+        let config = Config {
+            documentation: Some(true),
+            ..Config::default()
+        };
+        langs.get_statistics(&[data_dir], &[], &config);
+        assert!(!langs.is_empty());
 
         // Check that the value matches after serializing and deserializing
         for variant in Format::iter() {
             let serialized = variant
-                .print(&langs)
+                .print(&langs, &config)
                 .unwrap_or_else(|_| panic!("Failed serializing variant: {:?}", variant));
             let deserialized = Format::parse(&serialized)
                 .unwrap_or_else(|| panic!("Failed deserializing variant: {:?}", variant));
             assert_eq!(*langs, deserialized);
         }
     }
+
+    #[test]
+    fn grouped_print_matches_parse() {
+        let data_dir = Path::new("tests").join("data");
+        let mut langs = Languages::new();
+        let config = Config {
+            documentation: Some(true),
+            ..Config::default()
+        };
+        langs.get_statistics(&[data_dir], &[], &config);
+
+        for variant in Format::iter() {
+            let serialized = variant
+                .print_groups(&langs, &config)
+                .unwrap_or_else(|_| panic!("Failed serializing variant: {:?}", variant));
+            let deserialized = Format::parse(&serialized)
+                .unwrap_or_else(|| panic!("Failed deserializing variant: {:?}", variant));
+            assert_eq!(*langs, deserialized);
+        }
+
+        let printed = Format::Json.print_groups(&langs, &config).unwrap();
+        let groups: serde_json::Value = serde_json::from_str(&printed).unwrap();
+        assert!(groups["C family"]["languages"]["C"].is_object());
+        assert!(groups["Rust"]["languages"]["Rust"].is_object());
+    }
+
+    #[test]
+    fn print_languages_json() {
+        let printed = Format::Json.print_languages().unwrap();
+        let languages: serde_json::Value = serde_json::from_str(&printed).unwrap();
+
+        assert_eq!(languages["Rust"]["extensions"], serde_json::json!(["rs"]));
+        assert_eq!(languages["JSON"]["category"], "data");
+        assert_eq!(
+            languages["HTML"]["contexts"][0],
+            serde_json::json!(["<script", "</script>"])
+        );
+    }
 }
@@ -0,0 +1,79 @@
+//! Detecting binary files that ended up with the extension of a language,
+//! e.g. an MPEG transport stream named `.ts`, before they're read as text.
+
+use std::{error::Error, fmt, io};
+
+/// How many bytes at the start of a file are checked, the same as git.
+pub(crate) const BLOCK_SIZE: u64 = 8000;
+
+/// Files are considered binary when more than one in this many bytes of the
+/// first block are control characters.
+const CONTROL_RATIO: usize = 10;
+
+/// The error returned by [`LanguageType::parse`] for binary files.
+///
+/// [`LanguageType::parse`]: ../enum.LanguageType.html#method.parse
+#[derive(Debug)]
+pub(crate) struct BinaryFile;
+
+impl fmt::Display for BinaryFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("binary file")
+    }
+}
+
+impl Error for BinaryFile {}
+
+impl BinaryFile {
+    pub(crate) fn error() -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, BinaryFile)
+    }
+
+    /// Whether `error` is from skipping a binary file.
+    pub(crate) fn is(error: &io::Error) -> bool {
+        error.get_ref().is_some_and(|e| e.is::<BinaryFile>())
+    }
+}
+
+/// Whether `block`, the start of a file, looks like binary data: it contains
+/// a NUL byte, or too many control characters. Text with a UTF-16 or UTF-32
+/// byte order mark is never binary.
+pub(crate) fn is_binary(block: &[u8]) -> bool {
+    const BOMS: &[&[u8]] = &[
+        b"\xFF\xFE\x00\x00",
+        b"\x00\x00\xFE\xFF",
+        b"\xFF\xFE",
+        b"\xFE\xFF",
+    ];
+
+    if block.is_empty() || BOMS.iter().any(|bom| block.starts_with(bom)) {
+        return false;
+    }
+
+    if block.contains(&0) {
+        return true;
+    }
+
+    let control = block
+        .iter()
+        .filter(|&&b| (b < 0x20 && !b"\t\n\r\x0C\x1B".contains(&b)) || b == 0x7F)
+        .count();
+
+    control * CONTROL_RATIO > block.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks() {
+        assert!(!is_binary(b""));
+        assert!(!is_binary(b"fn main() {}\r\n\tlet a = 1;\x0C\n"));
+        assert!(!is_binary("const π = 3.14;\n".as_bytes()));
+        assert!(!is_binary(b"\xFF\xFEf\x00n\x00"));
+        assert!(is_binary(b"\x47\x40\x00\x10\x00\x00\xB0\x0D"));
+        assert!(is_binary(b"\x01\x02\x03\x04\x05\x06abc"));
+        assert!(!is_binary(b"\x1B[31mred\x1B[0m and plain text\n"));
+    }
+}
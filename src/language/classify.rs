@@ -0,0 +1,228 @@
+//! Where the lines counted by the parser go, so that the same state machine
+//! both totals a file and classifies each of its lines.
+
+use grep_searcher::LineIter;
+use rayon::prelude::*;
+
+use crate::{
+    config::Config,
+    language::LanguageType,
+    stats::{CodeStats, LineClass, LineKind},
+};
+
+/// A destination for counted lines: the totals of [`CodeStats`], or a
+/// [`LineClass`] for each line in order. Lines are numbered from 1.
+pub(crate) trait LineSink: Default + Send {
+    /// Adds the `line` of the language itself.
+    fn push(&mut self, line: usize, kind: LineKind);
+
+    /// Adds a `line` of code of the language itself that also contains a
+    /// comment, see `CodeStats.mixed`.
+    fn push_mixed(&mut self, line: usize);
+
+    /// Adds `count` logical lines that end on the `line` of code that was
+    /// just added, see `CodeStats.logical`.
+    fn push_logical(&mut self, line: usize, count: usize);
+
+    /// Adds a `line` of the language itself that's a doc comment, see
+    /// `CodeStats.docs`.
+    fn push_doc(&mut self, line: usize);
+
+    /// Counts the comments of the language itself as doc comments, e.g. the
+    /// Markdown of Rust's `///` comments.
+    fn into_docs(self) -> Self;
+
+    /// Adds the lines of a block of an embedded `language`, which starts
+    /// after `offset` lines of the text.
+    fn push_child(&mut self, language: LanguageType, offset: usize, child: Self);
+
+    /// Adds lines of the same language that were counted separately, and
+    /// start after `offset` lines of the text.
+    fn append(&mut self, offset: usize, other: Self);
+
+    /// Counts lines that can be classified on their own, without any state,
+    /// by calling `push` with the number and text of each line.
+    fn from_simple_lines<F>(text: &[u8], push: F) -> Self
+    where
+        F: Fn(&mut Self, usize, &[u8]) + Sync;
+
+    /// Counts the cells of a Jupyter notebook.
+    fn from_jupyter(json: &[u8], config: &Config) -> Self;
+}
+
+impl LineSink for CodeStats {
+    fn push(&mut self, _: usize, kind: LineKind) {
+        match kind {
+            LineKind::Code => self.code += 1,
+            LineKind::Comment => self.comments += 1,
+            LineKind::Blank => self.blanks += 1,
+        }
+    }
+
+    fn push_mixed(&mut self, _: usize) {
+        self.code += 1;
+        self.mixed = Some(self.mixed.unwrap_or(0) + 1);
+    }
+
+    fn push_logical(&mut self, _: usize, count: usize) {
+        self.logical = Some(self.logical.unwrap_or(0) + count);
+    }
+
+    fn push_doc(&mut self, _: usize) {
+        self.comments += 1;
+        self.docs += 1;
+    }
+
+    fn into_docs(mut self) -> Self {
+        self.docs = self.comments;
+        self
+    }
+
+    fn push_child(&mut self, language: LanguageType, _: usize, child: Self) {
+        *self.blobs.entry(language).or_default() += child;
+    }
+
+    fn append(&mut self, _: usize, other: Self) {
+        *self += other;
+    }
+
+    fn from_simple_lines<F>(text: &[u8], push: F) -> Self
+    where
+        F: Fn(&mut Self, usize, &[u8]) + Sync,
+    {
+        LineIter::new(b'\n', text)
+            .par_bridge()
+            .map(|line| {
+                let mut stats = CodeStats::new();
+                push(&mut stats, 0, line);
+                stats
+            })
+            .reduce(CodeStats::new, |mut a, b| {
+                a += b;
+                a
+            })
+    }
+
+    fn from_jupyter(json: &[u8], config: &Config) -> Self {
+        let mut jupyter_stats = CodeStats::new();
+        let cells: Vec<_> = LanguageType::jupyter_cells(json)
+            .unwrap_or_default()
+            .into_par_iter()
+            .map(|(language, source)| (language, language.parse_from_str(source, config)))
+            .collect();
+
+        for (language, stats) in cells {
+            *jupyter_stats.blobs.entry(language).or_default() += &stats;
+            jupyter_stats += &stats;
+        }
+
+        jupyter_stats
+    }
+}
+
+impl LineSink for Vec<LineClass> {
+    fn push(&mut self, line: usize, kind: LineKind) {
+        self.push(LineClass::new(line, kind));
+    }
+
+    fn push_mixed(&mut self, line: usize) {
+        self.push(LineClass {
+            mixed: true,
+            ..LineClass::new(line, LineKind::Code)
+        });
+    }
+
+    fn push_logical(&mut self, line: usize, count: usize) {
+        if let Some(class) = self.last_mut().filter(|class| class.line == line) {
+            class.logical += count;
+        }
+    }
+
+    fn push_doc(&mut self, line: usize) {
+        self.push(LineClass {
+            doc: true,
+            ..LineClass::new(line, LineKind::Comment)
+        });
+    }
+
+    fn into_docs(mut self) -> Self {
+        for class in &mut self {
+            class.doc |= class.kind == LineKind::Comment && class.language.is_none();
+        }
+        self
+    }
+
+    fn push_child(&mut self, language: LanguageType, offset: usize, child: Self) {
+        self.extend(child.into_iter().map(|class| LineClass {
+            line: class.line + offset,
+            language: class.language.or(Some(language)),
+            ..class
+        }));
+    }
+
+    fn append(&mut self, offset: usize, other: Self) {
+        self.extend(other.into_iter().map(|class| LineClass {
+            line: class.line + offset,
+            ..class
+        }));
+    }
+
+    fn from_simple_lines<F>(text: &[u8], push: F) -> Self
+    where
+        F: Fn(&mut Self, usize, &[u8]) + Sync,
+    {
+        let mut classes = Vec::new();
+        for (i, line) in LineIter::new(b'\n', text).enumerate() {
+            push(&mut classes, i + 1, line);
+        }
+        classes
+    }
+
+    /// Each line of a cell is listed once, as the language of the cell, and
+    /// numbered as if the cells were one after another.
+    fn from_jupyter(json: &[u8], config: &Config) -> Self {
+        let mut classes = Vec::new();
+        let mut offset = 0;
+
+        for (language, source) in LanguageType::jupyter_cells(json).unwrap_or_default() {
+            let cell = language.classify_lines(&source, config);
+            LineSink::push_child(&mut classes, language, offset, cell);
+            offset += LineIter::new(b'\n', source.as_bytes()).count();
+        }
+
+        classes
+    }
+}
+
+/// Finds the number of the line at byte offsets of a text, counting the line
+/// endings since the previous offset.
+pub(crate) struct LineNumbers<'a> {
+    text: &'a [u8],
+    offset: usize,
+    line: usize,
+}
+
+impl<'a> LineNumbers<'a> {
+    pub(crate) fn new(text: &'a [u8]) -> Self {
+        Self {
+            text,
+            offset: 0,
+            line: 1,
+        }
+    }
+
+    /// The number of the line containing `offset`.
+    pub(crate) fn at(&mut self, offset: usize) -> usize {
+        if offset < self.offset {
+            self.offset = 0;
+            self.line = 1;
+        }
+
+        self.line += self.text[self.offset..offset]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count();
+        self.offset = offset;
+        self.line
+    }
+}
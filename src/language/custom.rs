@@ -0,0 +1,339 @@
+//! Languages defined at runtime through the `[languages]` table of the
+//! configuration, see [`LanguageConfig`].
+
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+
+use super::{
+    raw_template, shebang,
+    syntax::{LogicalSyntax, SharedMatchers},
+};
+use crate::{
+    category::Category,
+    config::{Config, LanguageConfig},
+    language::LanguageType,
+};
+
+/// The registered languages, indexed by the id in [`LanguageType::Custom`].
+/// Entries are leaked so that they can hand out the same `'static` syntax as
+/// the languages generated from `languages.json`, and are only ever replaced
+/// when a language is registered again with a different definition.
+static REGISTRY: Lazy<RwLock<Vec<&'static CustomLanguage>>> = Lazy::new(Default::default);
+
+#[derive(Debug)]
+pub(crate) struct CustomLanguage {
+    source: LanguageConfig,
+    pub name: &'static str,
+    pub line_comments: &'static [&'static str],
+    pub multi_line_comments: &'static [(&'static str, &'static str)],
+    pub doc_line_comments: &'static [&'static str],
+    pub doc_declarations: &'static [&'static str],
+    pub doc_multi_line_comments: &'static [(&'static str, &'static str)],
+    pub logical_syntax: Option<LogicalSyntax>,
+    pub nested_comments: &'static [(&'static str, &'static str)],
+    pub any_multi_line_comments: &'static [(&'static str, &'static str)],
+    pub raw_comments: &'static [(&'static str, &'static str)],
+    pub any_comments: &'static [&'static str],
+    pub quotes: &'static [(&'static str, &'static str)],
+    pub verbatim_quotes: &'static [(&'static str, &'static str)],
+    pub raw_quotes: &'static [(&'static str, &'static str)],
+    pub heredocs: &'static [&'static str],
+    pub interpolations: &'static [(&'static str, &'static str, &'static str)],
+    pub doc_quotes: &'static [(&'static str, &'static str)],
+    pub important_syntax: &'static [&'static str],
+    pub shebangs: &'static [&'static str],
+    pub extensions: &'static [&'static str],
+    pub filenames: &'static [&'static str],
+    pub env: &'static [&'static str],
+    pub allows_nested: bool,
+    pub literate: bool,
+    pub category: Category,
+}
+
+fn leak_str(s: &str) -> &'static str {
+    Box::leak(s.into())
+}
+
+fn leak_strs<'a>(strings: impl IntoIterator<Item = &'a str>) -> &'static [&'static str] {
+    Box::leak(strings.into_iter().map(leak_str).collect())
+}
+
+fn leak_pairs(pairs: &[(String, String)]) -> &'static [(&'static str, &'static str)] {
+    let mut pairs: Vec<_> = pairs
+        .iter()
+        .map(|(start, end)| (leak_str(start), leak_str(end)))
+        .collect();
+    // Like `build.rs`, try the longest delimiters first so that e.g. `"""`
+    // isn't mistaken for `"`.
+    pairs.sort_by_key(|(start, end)| std::cmp::Reverse(start.len().max(end.len())));
+    Box::leak(pairs.into())
+}
+
+/// The raw templates that can be parsed. The others are rejected with a
+/// warning, as `build.rs` rejects them in `languages.json`.
+fn raw_templates(name: &str, templates: &[(String, String)]) -> Vec<(String, String)> {
+    templates
+        .iter()
+        .filter(|(start, end)| match raw_template::parse(start, end) {
+            Ok(_) => true,
+            Err(error) => {
+                warn!("The raw template {:?} of {} {}.", (start, end), name, error);
+                false
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+impl CustomLanguage {
+    fn new(name: &str, source: &LanguageConfig) -> Self {
+        let multi_line_comments = leak_pairs(&source.multi_line_comments);
+        let nested_comments = leak_pairs(&source.nested_comments);
+        let any_multi_line_comments = leak_pairs(
+            &source
+                .multi_line_comments
+                .iter()
+                .chain(&source.nested_comments)
+                .cloned()
+                .collect::<Vec<_>>(),
+        );
+        let quotes = leak_pairs(&source.quotes);
+        let verbatim_quotes = leak_pairs(&source.verbatim_quotes);
+        let raw_quotes = leak_pairs(&raw_templates(name, &source.raw_quotes));
+        let raw_comments = leak_pairs(&raw_templates(name, &source.raw_comments));
+        let heredocs = leak_strs(source.heredocs.iter().map(String::as_str));
+        let doc_quotes = leak_pairs(&source.doc_quotes);
+
+        let any_comments = leak_strs(
+            any_multi_line_comments
+                .iter()
+                .flat_map(|&(start, end)| [start, end])
+                .chain(source.line_comment.iter().map(String::as_str)),
+        );
+        let important_syntax = leak_strs(
+            quotes
+                .iter()
+                .chain(verbatim_quotes)
+                .chain(doc_quotes)
+                .chain(any_multi_line_comments)
+                .map(|&(start, _)| start)
+                .chain(
+                    raw_quotes
+                        .iter()
+                        .chain(raw_comments)
+                        .filter_map(|&(start, end)| raw_template::parse(start, end).ok())
+                        .map(|(start, _)| start.important_syntax()),
+                )
+                .chain(heredocs.iter().copied()),
+        );
+
+        Self {
+            source: source.clone(),
+            name: leak_str(name),
+            line_comments: leak_strs(source.line_comment.iter().map(String::as_str)),
+            multi_line_comments,
+            doc_line_comments: leak_strs(source.doc_line_comment.iter().map(String::as_str)),
+            doc_declarations: leak_strs(source.doc_declarations.iter().map(String::as_str)),
+            doc_multi_line_comments: leak_pairs(&source.doc_multi_line_comments),
+            logical_syntax: source.logical_lines.as_ref().map(|logical| LogicalSyntax {
+                terminators: leak_strs(logical.terminators.iter().map(String::as_str)),
+                blocks: leak_strs(logical.blocks.iter().map(String::as_str)),
+                directives: leak_strs(logical.directives.iter().map(String::as_str)),
+                continuation: logical.continuation.as_deref().map(leak_str),
+            }),
+            nested_comments,
+            any_multi_line_comments,
+            raw_comments,
+            any_comments,
+            quotes,
+            verbatim_quotes,
+            raw_quotes,
+            heredocs,
+            interpolations: Box::leak(
+                source
+                    .interpolations
+                    .iter()
+                    .map(|(quote, start, end)| (leak_str(quote), leak_str(start), leak_str(end)))
+                    .collect(),
+            ),
+            doc_quotes,
+            important_syntax,
+            shebangs: leak_strs(source.shebangs.iter().map(String::as_str)),
+            extensions: leak_strs(source.extensions.iter().map(String::as_str)),
+            filenames: leak_strs(source.filenames.iter().map(String::as_str)),
+            env: leak_strs(source.env.iter().map(String::as_str)),
+            allows_nested: source.nested,
+            literate: source.literate,
+            category: source.category.unwrap_or_default(),
+        }
+    }
+}
+
+/// Returns the registered language with the given id.
+pub(crate) fn get(id: u16) -> Option<&'static CustomLanguage> {
+    REGISTRY.read().get(usize::from(id)).copied()
+}
+
+/// Finds a registered language by its name, ignoring case.
+pub(crate) fn from_name(name: &str) -> Option<LanguageType> {
+    REGISTRY
+        .read()
+        .iter()
+        .position(|language| language.name.eq_ignore_ascii_case(name))
+        .map(into_language_type)
+}
+
+fn into_language_type(id: usize) -> LanguageType {
+    LanguageType::Custom(u16::try_from(id).expect("Too many custom languages registered."))
+}
+
+/// Registers a language under `name`, returning its [`LanguageType`].
+/// Registering a name again returns the same `LanguageType`, replacing the
+/// syntax if the definition has changed.
+pub(crate) fn register(name: &str, source: &LanguageConfig) -> LanguageType {
+    if let Some(language) = from_name(name).filter(|language| {
+        matches!(language, LanguageType::Custom(id) if get(*id).is_some_and(|l| l.source == *source))
+    }) {
+        return language;
+    }
+
+    let definition = &*Box::leak(Box::new(CustomLanguage::new(name, source)));
+    let mut registry = REGISTRY.write();
+    let id = match registry
+        .iter()
+        .position(|language| language.name.eq_ignore_ascii_case(name))
+    {
+        Some(id) => {
+            registry[id] = definition;
+            id
+        }
+        None => {
+            registry.push(definition);
+            registry.len() - 1
+        }
+    };
+    drop(registry);
+
+    let language = into_language_type(id);
+    SharedMatchers::invalidate(language);
+    language
+}
+
+/// Get a custom language defined in `config` from the shebang of a file
+/// without an extension. May open and read the file.
+pub(crate) fn from_shebang(entry: &Path, config: &Config) -> Option<LanguageType> {
+    let languages = config.languages.as_ref()?;
+    let custom_languages: Vec<_> = languages
+        .iter()
+        .filter(|(name, _)| LanguageType::from_builtin_name(name).is_none())
+        .filter(|(_, l)| !l.shebangs.is_empty() || !l.env.is_empty())
+        .collect();
+
+    if custom_languages.is_empty() {
+        return None;
+    }
+
+    let first_line = shebang::read_first_line(entry)?;
+    let interpreter = shebang::interpreter(&first_line)?;
+    let runs = |language: &LanguageConfig, interpreter: &str| {
+        language
+            .shebangs
+            .iter()
+            .filter_map(|s| shebang::interpreter(s))
+            .chain(language.env.iter().map(String::as_str))
+            .any(|i| i == interpreter)
+    };
+
+    // Like `LanguageType::from_shebang`, only strip the version when nothing
+    // matches the interpreter as it is.
+    let candidates = [interpreter, shebang::strip_version(interpreter)];
+    candidates
+        .iter()
+        .find_map(|interpreter| {
+            custom_languages
+                .iter()
+                .find(|(_, language)| runs(language, interpreter))
+        })
+        .map(|(name, language)| register(name, language))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acme() -> LanguageConfig {
+        LanguageConfig {
+            line_comment: vec!["#".into()],
+            multi_line_comments: vec![("(*".into(), "*)".into())],
+            quotes: vec![("\"".into(), "\"".into())],
+            extensions: vec!["acme".into()],
+            ..LanguageConfig::default()
+        }
+    }
+
+    #[test]
+    fn registering_is_idempotent() {
+        let language = register("RegisteringIsIdempotent", &acme());
+
+        assert_eq!(language, register("RegisteringIsIdempotent", &acme()));
+        assert_eq!(
+            Some(language),
+            LanguageType::from_name("RegisteringIsIdempotent")
+        );
+        assert_eq!(language.name(), "RegisteringIsIdempotent");
+    }
+
+    #[test]
+    fn custom_syntax_is_counted() {
+        let language = register("CustomSyntaxIsCounted", &acme());
+        let stats = language.parse_from_str(
+            "# A comment\nx = \"# not a comment\"\n\n(* multi\nline *)\ny = 1 # trailing\n",
+            &Config::default(),
+        );
+
+        assert_eq!(stats.code, 2);
+        assert_eq!(stats.comments, 3);
+        assert_eq!(stats.blanks, 1);
+    }
+
+    #[test]
+    fn invalid_raw_templates_are_ignored() {
+        let mut raw = acme();
+        raw.raw_quotes = vec![
+            ("r\"".into(), "\"".into()),
+            ("r{#}\"".into(), "\"{#}".into()),
+        ];
+        let language = register("InvalidRawTemplatesAreIgnored", &raw);
+
+        assert_eq!(language.raw_quotes(), &[("r{#}\"", "\"{#}")]);
+        assert_eq!(
+            language
+                .parse_from_str("x = r#\"\n# y\n\"#\n", &Config::default())
+                .code,
+            3
+        );
+    }
+
+    #[test]
+    fn reregistering_replaces_syntax() {
+        let language = register("ReregisteringReplacesSyntax", &acme());
+        assert_eq!(
+            language
+                .parse_from_str("// x\n", &Config::default())
+                .comments,
+            0
+        );
+
+        let mut slashes = acme();
+        slashes.line_comment = vec!["//".into()];
+        assert_eq!(language, register("ReregisteringReplacesSyntax", &slashes));
+        assert_eq!(
+            language
+                .parse_from_str("// x\n", &Config::default())
+                .comments,
+            1
+        );
+    }
+}
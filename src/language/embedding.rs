@@ -1,26 +1,178 @@
 #![allow(clippy::trivial_regex)]
 
-use crate::LanguageType;
+use crate::{stats::LineKind, utils::ext::SliceExt, LanguageType};
 use once_cell::sync::Lazy;
 use regex::bytes::Regex;
 
-pub static START_SCRIPT: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r#"<script(?:.*type="(.*)")?.*?>"#).unwrap());
-pub static END_SCRIPT: Lazy<Regex> = Lazy::new(|| Regex::new(r#"</script>"#).unwrap());
+pub static STARTING_LF_BLOCK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\{="#).unwrap());
+pub static ENDING_LF_BLOCK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"=}"#).unwrap());
 
-pub static START_STYLE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r#"<style(?:.*lang="(.*)")?.*?>"#).unwrap());
-pub static END_STYLE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"</style>"#).unwrap());
+/// A rule for the blocks of another language within a language, from the
+/// `embeddings` of `languages.json`.
+#[derive(Debug)]
+pub(crate) struct EmbeddingRule {
+    /// The text that ends the block, e.g. `</script>`.
+    pub end: &'static str,
+    /// A regex of the whole opening of the block, starting with the `start`
+    /// of the rule, e.g. `<script type="module">`, whose first group selects
+    /// its language.
+    pub opening: &'static str,
+    /// The language of the block, unless one is selected by its opening.
+    pub language: LanguageType,
+    /// How the first group of `opening` selects the language of the block.
+    pub select: Select,
+    /// How the line that the block starts on is counted.
+    pub delimiter: LineKind,
+    /// Whether blocks that end on the line they start on are counted as part
+    /// of that line, e.g. `<%= item %>`.
+    pub skip_inline: bool,
+}
 
-pub static START_TEMPLATE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r#"<template(?:.*lang="(.*)")?.*?>"#).unwrap());
-pub static END_TEMPLATE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"</template>"#).unwrap());
+/// How the language of an embedded block is selected from its opening.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Select {
+    /// Always the language of the rule.
+    Fixed,
+    /// A MIME type, e.g. the `type` attribute of `<script>`.
+    Mime,
+    /// A name, alias or extension of a language, e.g. the `lang` attribute of
+    /// `<style>`.
+    Name,
+}
 
-pub static STARTING_MARKDOWN_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"```\S+\s"#).unwrap());
-pub static ENDING_MARKDOWN_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"```\s?"#).unwrap());
+impl EmbeddingRule {
+    /// The language of a block whose opening captured `selector`.
+    pub(crate) fn language(&self, selector: Option<&[u8]>) -> LanguageType {
+        let selector = selector.map(|s| String::from_utf8_lossy(s.trim()).into_owned());
+        selector
+            .and_then(|selector| match self.select {
+                Select::Fixed => None,
+                Select::Mime => LanguageType::from_mime(&selector),
+                Select::Name => LanguageType::from_selector(&selector),
+            })
+            .unwrap_or(self.language)
+    }
+}
 
-pub static STARTING_LF_BLOCK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\{="#).unwrap());
-pub static ENDING_LF_BLOCK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"=}"#).unwrap());
+/// An [`EmbeddingRule`] with its regexes compiled, which are shared between
+/// the files of a language.
+#[derive(Clone, Debug)]
+pub(crate) struct Embedding {
+    pub rule: &'static EmbeddingRule,
+    pub opening: Regex,
+    pub end: Regex,
+}
+
+impl Embedding {
+    pub(crate) fn new(rule: &'static EmbeddingRule) -> Self {
+        Self {
+            rule,
+            opening: Regex::new(rule.opening).expect("Embeddings are validated by build.rs"),
+            end: Regex::new(&regex::escape(rule.end)).unwrap(),
+        }
+    }
+}
+
+/// The opening fence of a fenced code block in Markdown, following
+/// CommonMark, e.g. ```` ```rust ```` or `~~~~ {.python .numberLines}`.
+#[derive(Debug)]
+pub(crate) struct Fence<'a> {
+    /// Either `` ` `` or `~`.
+    character: u8,
+    /// The number of characters of the fence, at least three.
+    length: usize,
+    /// The column of the fence, which is more than three when the block is
+    /// within a list item.
+    column: usize,
+    /// The text after the fence, whose first word names the language.
+    info: &'a [u8],
+}
+
+impl<'a> Fence<'a> {
+    /// Parses the fence at the start of `rest`, where `prefix` is the text of
+    /// the line before it, which is only indentation or a list marker.
+    pub(crate) fn open(prefix: &[u8], rest: &'a [u8]) -> Option<Self> {
+        let character = *rest.first().filter(|&&c| c == b'`' || c == b'~')?;
+        let length = rest.iter().take_while(|&&c| c == character).count();
+        let info = rest[length..].trim();
+        if length < 3 || !is_list_marker(prefix) {
+            return None;
+        }
+
+        // A backtick in the info string means the line is inline code instead.
+        if character == b'`' && info.contains(&b'`') {
+            return None;
+        }
+
+        Some(Self {
+            character,
+            length,
+            column: indentation(prefix).1 + prefix.trim_start().len(),
+            info,
+        })
+    }
+
+    /// Whether `line` is a closing fence, made of at least as many of the
+    /// same characters and indented at most three columns past the opening.
+    pub(crate) fn is_closed_by(&self, line: &[u8]) -> bool {
+        let (_, column) = indentation(line);
+        let fence = line.trim();
+        column < self.column + 4
+            && fence.len() >= self.length
+            && fence.iter().all(|&c| c == self.character)
+    }
+
+    /// The language named by the first word of the info string, e.g. `rust`
+    /// in `rust,ignore`, `python` in `{.python .numberLines}`, or `r` in
+    /// `{r echo=FALSE}`, looked up by name, alias or extension.
+    pub(crate) fn language(&self) -> Option<LanguageType> {
+        let info = self.info.strip_prefix(b"{").unwrap_or(self.info);
+        let word = info
+            .split(|&c| c.is_ascii_whitespace() || c == b'}')
+            .find(|word| !word.is_empty())?;
+
+        String::from_utf8_lossy(word)
+            .split(',')
+            .map(|name| name.trim_start_matches('.'))
+            .filter(|name| !name.is_empty())
+            .find_map(LanguageType::from_selector)
+    }
+}
+
+/// Whether `prefix` is only indentation, or the marker of a list item
+/// followed by whitespace, e.g. `- ` or `1. `.
+fn is_list_marker(prefix: &[u8]) -> bool {
+    match prefix.trim() {
+        [] => true,
+        _ if !prefix.last().is_some_and(u8::is_ascii_whitespace) => false,
+        [b'-' | b'*' | b'+'] => true,
+        [digits @ .., b'.' | b')'] => {
+            (1..=9).contains(&digits.len()) && digits.iter().all(u8::is_ascii_digit)
+        }
+        _ => false,
+    }
+}
+
+/// The length and width of the indentation of `line`, with tabs stopping
+/// every four columns.
+fn indentation(line: &[u8]) -> (usize, usize) {
+    let mut column = 0;
+    let length = line
+        .iter()
+        .take_while(|&&c| match c {
+            b' ' => {
+                column += 1;
+                true
+            }
+            b'\t' => {
+                column += 4 - column % 4;
+                true
+            }
+            _ => false,
+        })
+        .count();
+    (length, column)
+}
 
 /// A memory of a regex matched.
 /// The values provided by `Self::start` and `Self::end` are in the same space as the
@@ -62,46 +214,87 @@ pub(crate) struct RegexCache<'a> {
 /// Embedding regexes are similar between different sets of languages.
 /// `RegexFamily` records both which family the language belongs to,
 /// as well as the actual matches
+///
+/// Only `Embedded` comes from the `embeddings` of `languages.json`. The
+/// others can't be written as a start, opening and end: a Markdown code
+/// fence is closed by a fence of the same character that's at least as long,
+/// and its info string selects the language from its first word; Rust's doc
+/// comments are a run of lines whose `///` or `//!` is stripped before they're
+/// counted as Markdown; and Lingua Franca's blocks are counted as the language
+/// of the file's `target` declaration, found outside of the block.
 pub(crate) enum RegexFamily<'a> {
-    HtmlLike(HtmlLike<'a>),
+    Embedded(Embedded<'a>),
     LinguaFranca(SimpleCapture<'a>),
-    Markdown(SimpleCapture<'a>),
+    Markdown,
     Rust,
 }
 
-pub(crate) struct HtmlLike<'a> {
-    start_script: Option<Box<[Capture<'a>]>>,
-    start_style: Option<Box<[Capture<'a>]>>,
-    start_template: Option<Box<[Capture<'a>]>>,
+/// The openings of the blocks declared by a language's embedding rules.
+pub(crate) struct Embedded<'a> {
+    openings: Box<[Opening<'a>]>,
+}
+
+/// The opening of a block matched by one of a language's [`Embedding`]s.
+pub(crate) struct Opening<'a> {
+    /// The index of the embedding that matched.
+    pub embedding: usize,
+    pub capture: Capture<'a>,
+    /// The first group of the opening, which selects the block's language.
+    pub selector: Option<Capture<'a>>,
 }
 
 pub(crate) struct SimpleCapture<'a> {
     starts: Option<Box<[Capture<'a>]>>,
 }
 
-impl<'a> HtmlLike<'a> {
-    pub fn start_script_in_range<'this>(
-        &'this self,
-        start: usize,
-        end: usize,
-    ) -> Option<impl Iterator<Item = &'this Capture<'a>>> {
-        filter_range(self.start_script.as_ref()?, start, end)
+impl<'a> Embedded<'a> {
+    /// The first opening of a block that starts at `start`.
+    pub fn opening_at(&self, start: usize) -> Option<&Opening<'a>> {
+        let pos = self
+            .openings
+            .binary_search_by_key(&start, |opening| opening.capture.start())
+            .ok()?;
+        // Several rules can match at the same place, so find the first one.
+        self.openings[..=pos]
+            .iter()
+            .rev()
+            .take_while(|opening| opening.capture.start() == start)
+            .last()
     }
 
-    pub fn start_style_in_range<'this>(
-        &'this self,
+    fn make_capture(
+        embeddings: &[Embedding],
+        lines: &'a [u8],
         start: usize,
         end: usize,
-    ) -> Option<impl Iterator<Item = &'this Capture<'a>>> {
-        filter_range(self.start_style.as_ref()?, start, end)
-    }
+    ) -> Option<Embedded<'a>> {
+        let capture = |m: regex::bytes::Match<'a>| Capture {
+            start: start + m.start(),
+            text: m.as_bytes(),
+        };
+        let mut openings: Vec<_> = embeddings
+            .iter()
+            .enumerate()
+            .flat_map(|(index, embedding)| {
+                embedding
+                    .opening
+                    .captures_iter(&lines[start..end])
+                    .map(move |captures| Opening {
+                        embedding: index,
+                        capture: capture(captures.get(0).unwrap()),
+                        selector: captures.get(1).map(capture),
+                    })
+            })
+            .collect();
 
-    pub fn start_template_in_range<'this>(
-        &'this self,
-        start: usize,
-        end: usize,
-    ) -> Option<impl Iterator<Item = &'this Capture<'a>>> {
-        filter_range(self.start_template.as_ref()?, start, end)
+        if openings.is_empty() {
+            None
+        } else {
+            openings.sort_by_key(|opening| opening.capture.start());
+            Some(Embedded {
+                openings: openings.into(),
+            })
+        }
     }
 }
 
@@ -152,46 +345,47 @@ fn filter_range<'dataset, 'cap>(
     }
 }
 
+/// The delimiters of the blocks of other languages that are counted within
+/// files of `language`, see `LanguageSyntax::contexts`.
+pub(crate) fn contexts(language: LanguageType) -> &'static [(&'static str, &'static str)] {
+    match language {
+        LanguageType::Markdown | LanguageType::UnrealDeveloperMarkdown => {
+            &[("```", "```"), ("~~~", "~~~")]
+        }
+        LanguageType::Rust => &[("///", "\n"), ("//!", "\n")],
+        LanguageType::LinguaFranca => &[("{=", "=}")],
+        _ => language.embedding_contexts(),
+    }
+}
+
 impl<'a> RegexCache<'a> {
     /// Returns the language family for which regexes were matched, if any
-    pub(crate) fn family(&self) -> Option<&RegexFamily> {
+    pub(crate) fn family(&self) -> Option<&RegexFamily<'_>> {
         self.inner.as_ref()
     }
 
     /// Tries to memoize any matches of embedding regexes that occur within lines[start..end]
     /// for the given language. Any `Capture` values eventually recovered will use the same
     /// zero for their start as the given `start` argument.
-    pub(crate) fn build(lang: LanguageType, lines: &'a [u8], start: usize, end: usize) -> Self {
+    pub(crate) fn build(
+        lang: LanguageType,
+        embeddings: &[Embedding],
+        lines: &'a [u8],
+        start: usize,
+        end: usize,
+    ) -> Self {
         let inner = match lang {
+            // Markdown can have front matter without any code blocks.
             LanguageType::Markdown | LanguageType::UnrealDeveloperMarkdown => {
-                SimpleCapture::make_capture(&STARTING_MARKDOWN_REGEX, lines, start, end)
-                    .map(RegexFamily::Markdown)
+                Some(RegexFamily::Markdown)
             }
             LanguageType::Rust => Some(RegexFamily::Rust),
             LanguageType::LinguaFranca => {
                 SimpleCapture::make_capture(&STARTING_LF_BLOCK_REGEX, lines, start, end)
                     .map(RegexFamily::LinguaFranca)
             }
-            LanguageType::Html
-            | LanguageType::RubyHtml
-            | LanguageType::Svelte
-            | LanguageType::Vue
-            | LanguageType::GlimmerJs
-            | LanguageType::GlimmerTs => {
-                let html = HtmlLike {
-                    start_script: save_captures(&START_SCRIPT, lines, start, end),
-                    start_style: save_captures(&START_STYLE, lines, start, end),
-                    start_template: save_captures(&START_TEMPLATE, lines, start, end),
-                };
-
-                if html.start_script.is_some()
-                    || html.start_style.is_some()
-                    || html.start_template.is_some()
-                {
-                    Some(RegexFamily::HtmlLike(html))
-                } else {
-                    None
-                }
+            _ if !embeddings.is_empty() => {
+                Embedded::make_capture(embeddings, lines, start, end).map(RegexFamily::Embedded)
             }
             _ => None,
         };
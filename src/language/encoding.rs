@@ -0,0 +1,74 @@
+//! Choosing the encoding that files are decoded from before being counted.
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+/// Get the encoding of a file from `block`, the start of it. A byte order
+/// mark takes precedence over the `configured` encoding, which takes
+/// precedence over detecting UTF-16 without a byte order mark. Anything else
+/// is read as UTF-8.
+pub(crate) fn detect(block: &[u8], configured: Option<&'static Encoding>) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(block) {
+        return encoding;
+    }
+
+    configured
+        .or_else(|| utf16_without_bom(block))
+        .unwrap_or(UTF_8)
+}
+
+/// Whether `encoding` has NUL bytes in ordinary text, and so can't be checked
+/// for binary data.
+pub(crate) fn is_utf16(encoding: &'static Encoding) -> bool {
+    encoding == UTF_16LE || encoding == UTF_16BE
+}
+
+/// Source code is mostly ASCII, which in UTF-16 has a NUL in every other
+/// byte, on the side of each pair matching its endianness.
+fn utf16_without_bom(block: &[u8]) -> Option<&'static Encoding> {
+    let pairs = block.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+
+    let (high, low) = block.chunks_exact(2).fold((0, 0), |(high, low), pair| {
+        (
+            high + usize::from(pair[0] == 0),
+            low + usize::from(pair[1] == 0),
+        )
+    });
+
+    if low * 2 > pairs && high * 20 < pairs {
+        Some(UTF_16LE)
+    } else if high * 2 > pairs && low * 20 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::SHIFT_JIS;
+
+    use super::*;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    fn utf16be(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    #[test]
+    fn detection() {
+        let text = "// Hello\nfn main() {}\n";
+
+        assert_eq!(detect(text.as_bytes(), None), UTF_8);
+        assert_eq!(detect(&utf16le(text), None), UTF_16LE);
+        assert_eq!(detect(&utf16be(text), None), UTF_16BE);
+        assert_eq!(detect(text.as_bytes(), Some(SHIFT_JIS)), SHIFT_JIS);
+        assert_eq!(detect(b"\xEF\xBB\xBFfn", Some(SHIFT_JIS)), UTF_8);
+        assert_eq!(detect(b"\x00\x01\x00\x02\x00\x00\x00", None), UTF_8);
+    }
+}
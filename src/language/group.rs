@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    language::{Language, LanguageType},
+    stats::add_optional,
+};
+
+/// The statistics of a group of languages counted as a single row, see
+/// [`Languages::groups`].
+///
+/// [`Languages::groups`]: struct.Languages.html#method.groups
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct LanguageGroup {
+    /// The total number of blank lines, including those of embedded languages.
+    pub blanks: usize,
+    /// The total number of lines of code, including those of embedded
+    /// languages.
+    pub code: usize,
+    /// The total number of comments, including those of embedded languages.
+    pub comments: usize,
+    /// The total number of comments that are documentation, including those
+    /// of embedded languages.
+    #[serde(default)]
+    pub docs: usize,
+    /// The total number of lines of code that also contain a comment, when
+    /// enabled with `Config.mixed`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mixed: Option<usize>,
+    /// The total number of logical lines, when enabled with
+    /// `Config.logical_lines`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logical: Option<usize>,
+    /// Whether any language in the group had problems with file parsing.
+    pub inaccurate: bool,
+    /// The statistics of each language in the group.
+    pub languages: BTreeMap<LanguageType, Language>,
+}
+
+impl LanguageGroup {
+    /// Constructs a new empty group.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the total number of lines.
+    #[inline]
+    #[must_use]
+    pub fn lines(&self) -> usize {
+        self.blanks + self.code + self.comments
+    }
+
+    /// Returns the number of files in the group.
+    #[must_use]
+    pub fn files(&self) -> usize {
+        self.languages.values().map(|l| l.reports.len()).sum()
+    }
+
+    /// Adds a language to the group, adding its statistics to the group's
+    /// totals.
+    pub fn add_language(&mut self, language_type: LanguageType, language: Language) {
+        let summary = language.summarise();
+        self.blanks += summary.blanks;
+        self.code += summary.code;
+        self.comments += summary.comments;
+        self.docs += summary.docs;
+        add_optional(&mut self.mixed, summary.mixed);
+        add_optional(&mut self.logical, summary.logical);
+        self.inaccurate |= summary.inaccurate;
+
+        match self.languages.get_mut(&language_type) {
+            Some(existing) => *existing += language,
+            None => {
+                self.languages.insert(language_type, language);
+            }
+        }
+    }
+}
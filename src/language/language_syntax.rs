@@ -0,0 +1,108 @@
+use crate::{category::Category, language::embedding, LanguageType};
+
+/// Every property of a language from `languages.json`, describing how files
+/// are matched to it and how its lines are counted.
+///
+/// ```
+/// use tokei::LanguageType;
+///
+/// let rust = LanguageType::Rust.syntax();
+///
+/// assert_eq!(rust.name, "Rust");
+/// assert_eq!(rust.extensions, &["rs"]);
+/// assert_eq!(rust.line_comments, &["//"]);
+/// assert!(rust.nested);
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LanguageSyntax {
+    /// The display name of the language.
+    pub name: &'static str,
+    /// The category of the language.
+    pub category: Category,
+    /// Whether the language is primarily documentation, and so is counted as
+    /// comments.
+    pub literate: bool,
+    /// Whether the language has no syntax for comments.
+    pub blank: bool,
+    /// File extensions of the language.
+    pub extensions: &'static [&'static str],
+    /// Whole file names of the language, in lowercase.
+    pub filenames: &'static [&'static str],
+    /// Globs matched against the whole path of a file.
+    pub globs: &'static [&'static str],
+    /// Whole shebang lines of the language.
+    pub shebangs: &'static [&'static str],
+    /// Programs that run the language through `#!/usr/bin/env`.
+    pub env: &'static [&'static str],
+    /// Other names of the language, e.g. in modelines.
+    pub aliases: &'static [&'static str],
+    /// MIME types of the language.
+    pub mime: &'static [&'static str],
+    /// Single line comments.
+    pub line_comments: &'static [&'static str],
+    /// Pairs of starting and ending multi line comments.
+    pub multi_line_comments: &'static [(&'static str, &'static str)],
+    /// Whether multi line comments can be nested.
+    pub nested: bool,
+    /// Pairs of multi line comments that can always be nested.
+    pub nested_comments: &'static [(&'static str, &'static str)],
+    /// Templates of multi line comments whose end depends on their start,
+    /// e.g. `--[{=}[` and `]{=}]` for Lua's `--[==[…]==]`.
+    pub raw_comments: &'static [(&'static str, &'static str)],
+    /// Pairs of starting and ending string literal quotes.
+    pub quotes: &'static [(&'static str, &'static str)],
+    /// Pairs of quotes for string literals that can't contain escapes.
+    pub verbatim_quotes: &'static [(&'static str, &'static str)],
+    /// Templates of string literal quotes whose closing quote depends on the
+    /// opening one, e.g. `r{#}"` and `"{#}` for `r#"…"#`.
+    pub raw_quotes: &'static [(&'static str, &'static str)],
+    /// Interpolations within strings, as the start of the string along with
+    /// the start and end of the interpolation, e.g. `` ` ``, `${` and `}`.
+    pub interpolations: &'static [(&'static str, &'static str, &'static str)],
+    /// Operators that open a heredoc, e.g. `<<`.
+    pub heredocs: &'static [&'static str],
+    /// Pairs of quotes for doc strings.
+    pub doc_quotes: &'static [(&'static str, &'static str)],
+    /// Syntax that starts something other than code, e.g. a comment or a
+    /// string literal.
+    pub important_syntax: &'static [&'static str],
+    /// Pairs of delimiters of the blocks of other languages counted within
+    /// the language, e.g. `<script` and `</script>` in HTML. A `"\n"` ends
+    /// the block at the end of the line.
+    pub contexts: &'static [(&'static str, &'static str)],
+}
+
+impl LanguageType {
+    /// Returns every property of the language, see [`LanguageSyntax`].
+    ///
+    /// [`LanguageSyntax`]: struct.LanguageSyntax.html
+    pub fn syntax(self) -> LanguageSyntax {
+        LanguageSyntax {
+            name: self.name(),
+            category: self.category(),
+            literate: self.is_literate(),
+            blank: self.is_blank(),
+            extensions: self.extensions(),
+            filenames: self.filenames(),
+            globs: self.globs(),
+            shebangs: self.shebangs(),
+            env: self.env(),
+            aliases: self.aliases(),
+            mime: self.mime(),
+            line_comments: self.line_comments(),
+            multi_line_comments: self.multi_line_comments(),
+            nested: self.allows_nested(),
+            nested_comments: self.nested_comments(),
+            raw_comments: self.raw_comments(),
+            quotes: self.quotes(),
+            verbatim_quotes: self.verbatim_quotes(),
+            raw_quotes: self.raw_quotes(),
+            heredocs: self.heredocs(),
+            interpolations: self.interpolations(),
+            doc_quotes: self.doc_quotes(),
+            important_syntax: self.important_syntax(),
+            contexts: embedding::contexts(self),
+        }
+    }
+}
@@ -8,17 +8,23 @@ use std::{
 };
 
 use crate::{
+    category::Category,
     config::Config,
-    language::syntax::{FileContext, LanguageContext, SyntaxCounter},
-    stats::{CodeStats, Report},
-    utils::{ext::SliceExt, fs as fsutils},
+    language::classify::{LineNumbers, LineSink},
+    language::embedding::{EmbeddingRule, Select},
+    language::syntax::{
+        AnalysisReport, FileContext, LanguageContext, LogicalSyntax, SyntaxCounter,
+    },
+    language::{binary, custom, encoding, modeline, shebang, vendored},
+    stats::{CodeStats, LineClass, LineKind, Report},
+    utils::{ext::SliceExt, fs as fsutils, gitattributes::GitAttributes, glob},
 };
 
+use encoding_rs::{Encoding, UTF_8};
 use encoding_rs_io::DecodeReaderBytesBuilder;
 use grep_searcher::{LineIter, LineStep};
 use once_cell::sync::Lazy;
-use rayon::prelude::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use self::LanguageType::*;
 
@@ -33,31 +39,120 @@ impl Serialize for LanguageType {
     }
 }
 
+impl<'de> Deserialize<'de> for LanguageType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Cow::<'de, str>::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 impl LanguageType {
+    /// The error for a language `name` that wasn't found, suggesting the
+    /// closest name or alias.
+    pub(crate) fn unknown_name_error(name: &str) -> String {
+        match Self::closest_name(name) {
+            Some(language) => format!(
+                "Unknown language `{}`, did you mean `{}`? Use `-l` to see all available \
+                 languages.",
+                name, language
+            ),
+            None => format!(
+                "Unknown language `{}`, use `-l` to see all available languages.",
+                name
+            ),
+        }
+    }
+
+    /// Get the language with the name or alias closest to `name`, if any is
+    /// close enough to be a likely typo.
+    fn closest_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let max_distance = (name.chars().count() / 3).max(1);
+
+        Self::ALIASES
+            .iter()
+            .map(|&(alias, language)| (edit_distance(&name, alias), language))
+            .filter(|&(distance, _)| distance <= max_distance)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, language)| language)
+    }
+
     /// Parses a given [`Path`] using the [`LanguageType`]. Returning [`Report`]
-    /// on success and giving back ownership of [`PathBuf`] on error.
+    /// on success and giving back ownership of [`PathBuf`] on error. Binary
+    /// files are detected from their first few kilobytes, and give an error of
+    /// the kind [`io::ErrorKind::InvalidData`] without being read any further.
+    /// Files are decoded from the encoding given by a byte order mark,
+    /// `Config.encodings`, or `Config.encoding`, or from UTF-16 when detected,
+    /// and otherwise read as UTF-8.
     pub fn parse(self, path: PathBuf, config: &Config) -> Result<Report, (io::Error, PathBuf)> {
-        let text = {
-            let f = match File::open(&path) {
-                Ok(f) => f,
-                Err(e) => return Err((e, path)),
-            };
-            let mut s = Vec::new();
-            let mut reader = DecodeReaderBytesBuilder::new().build(f);
-
-            if let Err(e) = reader.read_to_end(&mut s) {
-                return Err((e, path));
-            }
-            s
+        let (text, encoding) = match Self::read(&path, config) {
+            Ok(read) => read,
+            Err(e) => return Err((e, path)),
         };
 
         let mut stats = Report::new(path);
+        stats.kind = vendored::from_contents(self, &text);
+        stats.encoding = Some(encoding.name().to_owned());
 
         stats += self.parse_from_slice(text, config);
 
         Ok(stats)
     }
 
+    /// Reads the text of the file at `path` the same way as
+    /// [`LanguageType::parse`], decoded from its encoding, and with the same
+    /// error for binary files.
+    ///
+    /// [`LanguageType::parse`]: enum.LanguageType.html#method.parse
+    pub fn read_text<P: AsRef<Path>>(path: P, config: &Config) -> io::Result<Vec<u8>> {
+        Self::read(path.as_ref(), config).map(|(text, _)| text)
+    }
+
+    fn read(path: &Path, config: &Config) -> io::Result<(Vec<u8>, &'static Encoding)> {
+        let mut f = File::open(path)?;
+
+        // Only the first block is read before checking for binary files, to
+        // avoid reading the rest of them.
+        let mut block = Vec::new();
+        f.by_ref()
+            .take(binary::BLOCK_SIZE)
+            .read_to_end(&mut block)?;
+
+        let encoding = encoding::detect(&block, config.encoding_for(path));
+        if !encoding::is_utf16(encoding) && binary::is_binary(&block) {
+            return Err(binary::BinaryFile::error());
+        }
+
+        let mut builder = DecodeReaderBytesBuilder::new();
+        if encoding != UTF_8 {
+            builder.encoding(Some(encoding));
+        }
+
+        let mut text = Vec::new();
+        builder
+            .build(io::Cursor::new(block).chain(f))
+            .read_to_end(&mut text)?;
+        Ok((text, encoding))
+    }
+
+    /// Get the language of the file at `path` the same way as the files found
+    /// by [`Languages::get_statistics`], where `linguist-language` in
+    /// `.gitattributes` takes precedence over [`LanguageType::from_path`].
+    ///
+    /// [`Languages::get_statistics`]: struct.Languages.html#method.get_statistics
+    /// [`LanguageType::from_path`]: enum.LanguageType.html#method.from_path
+    pub fn from_file<P: AsRef<Path>>(path: P, config: &Config) -> Option<Self> {
+        let path = path.as_ref();
+        GitAttributes::new()
+            .for_path(path, 0)
+            .language
+            .or_else(|| LanguageType::from_path(path, config))
+    }
+
     /// Parses the text provided as the given [`LanguageType`].
     pub fn parse_from_str<A: AsRef<str>>(self, text: A, config: &Config) -> CodeStats {
         self.parse_from_slice(text.as_ref().as_bytes(), config)
@@ -65,23 +160,64 @@ impl LanguageType {
 
     /// Parses the bytes provided as the given [`LanguageType`].
     pub fn parse_from_slice<A: AsRef<[u8]>>(self, text: A, config: &Config) -> CodeStats {
-        let text = text.as_ref();
+        let mut stats: CodeStats = self.count_lines(text.as_ref(), config);
+        if config.mixed == Some(true) {
+            stats.mixed.get_or_insert(0);
+        }
+        if config.logical_lines == Some(true) && self.logical_syntax().is_some() {
+            stats.logical.get_or_insert(0);
+        }
+        stats
+    }
+
+    /// Classifies each line of the text provided as the given
+    /// [`LanguageType`], in order, using the same parser as
+    /// [`LanguageType::parse_from_slice`]. The lines of each kind add up to
+    /// its [`CodeStats`], including the lines of embedded languages, which
+    /// are listed with the language they were counted in.
+    ///
+    /// ```
+    /// use tokei::{Config, LineKind, LanguageType};
+    ///
+    /// let text = "// Hello\nfn main() {}\n\n";
+    /// let lines = LanguageType::Rust.classify_lines(text, &Config::default());
+    /// let kinds: Vec<_> = lines.iter().map(|line| line.kind).collect();
+    ///
+    /// assert_eq!(kinds, [LineKind::Comment, LineKind::Code, LineKind::Blank]);
+    /// ```
+    ///
+    /// [`LanguageType::parse_from_slice`]: enum.LanguageType.html#method.parse_from_slice
+    /// [`CodeStats`]: struct.CodeStats.html
+    pub fn classify_lines<A: AsRef<[u8]>>(self, text: A, config: &Config) -> Vec<LineClass> {
+        self.count_lines(text.as_ref(), config)
+    }
 
+    fn count_lines<S: LineSink>(self, text: &[u8], config: &Config) -> S {
         if self == Jupyter {
-            return self
-                .parse_jupyter(text.as_ref(), config)
-                .unwrap_or_default();
+            return S::from_jupyter(text, config);
         }
 
-        let syntax = {
+        let mut syntax = {
             let mut syntax_mut = SyntaxCounter::new(self);
             if self == LinguaFranca {
                 syntax_mut.lf_embedded_language = self.find_lf_target_language(text);
             }
+            syntax_mut.count_mixed = config.mixed == Some(true);
+            syntax_mut.count_logical =
+                config.logical_lines == Some(true) && syntax_mut.shared.logical_syntax.is_some();
             syntax_mut
         };
 
-        if let Some(end) = syntax.shared.important_syntax.find(text).and_then(|m| {
+        // Statements can continue onto the next line, and doc comments depend
+        // on the declaration after them, so both are only counted by parsing
+        // the lines in order.
+        let important_syntax = if syntax.count_logical || !syntax.shared.doc_declarations.is_empty()
+        {
+            None
+        } else {
+            syntax.shared.important_syntax.find(text)
+        };
+        if let Some(end) = important_syntax.and_then(|m| {
             // Get the position of the last line before the important
             // syntax.
             text[..=m.start()]
@@ -92,57 +228,65 @@ impl LanguageType {
                 .map(|p| m.start() - p)
         }) {
             let (skippable_text, rest) = text.split_at(end + 1);
+            syntax.starts_file = false;
             let is_fortran = syntax.shared.is_fortran;
             let is_literate = syntax.shared.is_literate;
             let comments = syntax.shared.line_comments;
+            let doc_comments = syntax.shared.doc_line_comments;
+            let count_mixed = syntax.count_mixed;
             trace!(
                 "Using Simple Parse on {:?}",
                 String::from_utf8_lossy(skippable_text)
             );
-            let parse_lines = move || self.parse_lines(config, rest, CodeStats::new(), syntax);
+            let parse_lines = move || self.parse_lines(config, rest, S::default(), syntax);
             let simple_parse = move || {
-                LineIter::new(b'\n', skippable_text)
-                    .par_bridge()
-                    .map(|line| {
-                        // FORTRAN has a rule where it only counts as a comment if it's the
-                        // first character in the column, so removing starting whitespace
-                        // could cause a miscount.
-                        let line = if is_fortran { line } else { line.trim() };
-                        if line.trim().is_empty() {
-                            (1, 0, 0)
-                        } else if is_literate
-                            || comments.iter().any(|c| line.starts_with(c.as_bytes()))
-                        {
-                            (0, 0, 1)
-                        } else {
-                            (0, 1, 0)
-                        }
-                    })
-                    .reduce(|| (0, 0, 0), |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2))
+                S::from_simple_lines(skippable_text, |stats, number, line| {
+                    // FORTRAN has a rule where it only counts as a comment if it's the
+                    // first character in the column, so removing starting whitespace
+                    // could cause a miscount.
+                    let line = if is_fortran { line } else { line.trim() };
+                    if line.trim().is_empty() {
+                        stats.push(number, LineKind::Blank);
+                    } else if is_literate {
+                        stats.push(number, LineKind::Comment);
+                    } else if doc_comments.iter().any(|c| line.starts_with(c.as_bytes())) {
+                        stats.push_doc(number);
+                    } else if comments.iter().any(|c| line.starts_with(c.as_bytes())) {
+                        stats.push(number, LineKind::Comment);
+                    } else if count_mixed
+                        && comments.iter().any(|c| line.contains_slice(c.as_bytes()))
+                    {
+                        stats.push_mixed(number);
+                    } else {
+                        stats.push(number, LineKind::Code);
+                    }
+                })
             };
 
-            let (mut stats, (blanks, code, comments)) = rayon::join(parse_lines, simple_parse);
+            let (rest, mut stats) = rayon::join(parse_lines, simple_parse);
 
-            stats.blanks += blanks;
-            stats.code += code;
-            stats.comments += comments;
+            stats.append(LineIter::new(b'\n', skippable_text).count(), rest);
             stats
         } else {
-            self.parse_lines(config, text, CodeStats::new(), syntax)
+            self.parse_lines(config, text, S::default(), syntax)
         }
     }
 
     #[inline]
-    fn parse_lines(
+    fn parse_lines<S: LineSink>(
         self,
         config: &Config,
         lines: &[u8],
-        mut stats: CodeStats,
+        mut stats: S,
         mut syntax: SyntaxCounter,
-    ) -> CodeStats {
+    ) -> S {
         let mut stepper = LineStep::new(b'\n', 0, lines.len());
+        let mut line_numbers = LineNumbers::new(lines);
+        // Line comments that might document the next line's declaration.
+        let mut doc_candidates = Vec::new();
 
         while let Some((start, end)) = stepper.next(lines) {
+            let number = line_numbers.at(start);
             let line = &lines[start..end];
             // FORTRAN has a rule where it only counts as a comment if it's the
             // first character in the column, so removing starting whitespace
@@ -154,41 +298,119 @@ impl LanguageType {
             };
             trace!("{}", String::from_utf8_lossy(line));
 
-            if syntax.try_perform_single_line_analysis(line, &mut stats) {
+            // The body of a heredoc is code, unless its tag names a language,
+            // and only ends at a line made of the tag.
+            if let Some(heredoc) = syntax.heredocs.first() {
+                if let Some(language) = heredoc.language {
+                    let mut body = LineStep::new(b'\n', start, lines.len());
+                    let mut end_of_body = start;
+                    let mut terminator = None;
+                    while let Some((start, end)) = body.next(lines) {
+                        if heredoc.is_terminator(&lines[start..end]) {
+                            terminator = Some((start, end));
+                            break;
+                        }
+                        end_of_body = end;
+                    }
+
+                    let child = language.count_lines(&lines[start..end_of_body], config);
+                    stats.push_child(language, number - 1, child);
+                    if let Some((start, end)) = terminator {
+                        stats.push(line_numbers.at(start), LineKind::Code);
+                        stepper = LineStep::new(b'\n', end, lines.len());
+                    } else {
+                        stepper = LineStep::new(b'\n', lines.len(), lines.len());
+                    }
+                    syntax.heredocs.remove(0);
+                } else {
+                    if heredoc.is_terminator(&lines[start..end]) {
+                        syntax.heredocs.remove(0);
+                    }
+                    stats.push(number, LineKind::Code);
+                }
+                trace!("Heredoc");
+                continue;
+            }
+
+            if syntax.is_doc_candidate(line) {
+                doc_candidates.push(number);
+                continue;
+            }
+            let documented = syntax.is_documented_declaration(&lines[start..end]);
+            for number in doc_candidates.drain(..) {
+                if documented {
+                    stats.push_doc(number);
+                } else {
+                    stats.push(number, LineKind::Comment);
+                }
+            }
+
+            if syntax.try_perform_single_line_analysis(line, number, &mut stats) {
                 continue;
             }
 
-            let started_in_comments = !syntax.stack.is_empty()
-                || (config.treat_doc_strings_as_comments == Some(true)
-                    && syntax.quote.is_some()
-                    && syntax.quote_is_doc_quote);
-            let ended_with_comments =
-                match syntax.perform_multi_line_analysis(lines, start, end, config) {
-                    crate::language::syntax::AnalysisReport::Normal(end) => end,
-                    crate::language::syntax::AnalysisReport::ChildLanguage(FileContext {
+            let started_in_doc_string = config.treat_doc_strings_as_comments == Some(true)
+                && syntax.quote.is_some()
+                && syntax.quote_is_doc_quote;
+            let started_in_comments = !syntax.stack.is_empty() || started_in_doc_string;
+            let started_in_docs =
+                (!syntax.stack.is_empty() && syntax.doc_comment) || started_in_doc_string;
+            let (ended_with_comments, has_comments, statements) =
+                match syntax.perform_multi_line_analysis(lines, start, end) {
+                    AnalysisReport::Normal {
+                        ended_with_comments,
+                        has_comments,
+                        statements,
+                    } => (ended_with_comments, has_comments, statements),
+                    AnalysisReport::ChildLanguage(FileContext {
                         language,
+                        text,
+                        start,
                         end,
-                        stats: blob,
                     }) => {
+                        let offset = line_numbers.at(start) - 1;
                         match language {
                             LanguageContext::Markdown { balanced, language } => {
-                                // Add the lines for the code fences.
-                                stats.comments += if balanced { 2 } else { 1 };
-                                // Add the code inside the fence to the stats.
-                                *stats.blobs.entry(language).or_default() += blob;
+                                // Add the lines for the code fences around the
+                                // code inside the fence.
+                                stats.push(number, LineKind::Comment);
+                                if let Some(language) = language {
+                                    let child = language.count_lines(&text, config);
+                                    stats.push_child(language, offset, child);
+                                } else {
+                                    let prose = if syntax.shared.is_literate {
+                                        LineKind::Comment
+                                    } else {
+                                        LineKind::Code
+                                    };
+                                    let child = S::from_simple_lines(&text, |s, n, line| {
+                                        let blank = line.trim().is_empty();
+                                        s.push(n, if blank { LineKind::Blank } else { prose });
+                                    });
+                                    stats.append(offset, child);
+                                }
+                                if balanced {
+                                    stats.push(line_numbers.at(end - 1), LineKind::Comment);
+                                }
                             }
                             LanguageContext::Rust => {
                                 // Add all the markdown blobs.
-                                *stats.blobs.entry(LanguageType::Markdown).or_default() += blob;
+                                let markdown = LanguageType::Markdown;
+                                let child: S = markdown.count_lines(&text, config);
+                                stats.push_child(markdown, offset, child.into_docs());
                             }
                             LanguageContext::LinguaFranca => {
                                 let child_lang = syntax.get_lf_target_language();
-                                *stats.blobs.entry(child_lang).or_default() += blob;
+                                let child = child_lang.count_lines(&text, config);
+                                stats.push_child(child_lang, offset, child);
                             }
-                            LanguageContext::Html { language } => {
-                                stats.code += 1;
-                                // Add all the markdown blobs.
-                                *stats.blobs.entry(language).or_default() += blob;
+                            LanguageContext::Embedded {
+                                language,
+                                delimiter,
+                            } => {
+                                stats.push(number, delimiter);
+                                let child = language.count_lines(&text, config);
+                                stats.push_child(language, offset, child);
                             }
                         }
 
@@ -199,22 +421,47 @@ impl LanguageType {
                 };
             trace!("{}", String::from_utf8_lossy(line));
 
-            if syntax.shared.is_literate
-                || syntax.line_is_comment(line, config, ended_with_comments, started_in_comments)
+            if syntax.shared.is_literate {
+                stats.push(number, LineKind::Comment);
+                trace!("Comment");
+            } else if syntax.line_is_comment(line, config, ended_with_comments, started_in_comments)
             {
-                stats.comments += 1;
-                trace!("Comment No.{}", stats.comments);
+                // Doc strings are documentation when they're counted as
+                // comments at all.
+                let starts_doc_string = config.treat_doc_strings_as_comments == Some(true)
+                    && syntax
+                        .shared
+                        .doc_quotes
+                        .iter()
+                        .any(|(start, _)| line.starts_with(start.as_bytes()));
+                if started_in_docs || starts_doc_string || syntax.starts_with_doc_comment(line) {
+                    stats.push_doc(number);
+                    trace!("Doc");
+                } else {
+                    stats.push(number, LineKind::Comment);
+                    trace!("Comment");
+                }
                 trace!("Was the Comment stack empty?: {}", !started_in_comments);
             } else {
-                stats.code += 1;
-                trace!("Code No.{}", stats.code);
+                if syntax.count_mixed && has_comments {
+                    stats.push_mixed(number);
+                    trace!("Mixed");
+                } else {
+                    stats.push(number, LineKind::Code);
+                    trace!("Code");
+                }
+                syntax.push_logical_lines(line, Some(statements), number, &mut stats);
             }
         }
 
+        for number in doc_candidates {
+            stats.push(number, LineKind::Comment);
+        }
         stats
     }
 
-    fn parse_jupyter(&self, json: &[u8], config: &Config) -> Option<CodeStats> {
+    /// The language and source of each cell of a Jupyter notebook.
+    pub(crate) fn jupyter_cells(json: &[u8]) -> Option<Vec<(LanguageType, String)>> {
         #[derive(Deserialize)]
         struct Jupyter {
             cells: Vec<JupyterCell>,
@@ -242,8 +489,6 @@ impl LanguageType {
 
         let jupyter: Jupyter = serde_json::from_slice(json).ok()?;
 
-        let mut jupyter_stats = CodeStats::new();
-
         let language = jupyter
             .metadata
             .kernelspec
@@ -260,27 +505,16 @@ impl LanguageType {
             })
             .unwrap_or(LanguageType::Python);
 
-        let iter = jupyter
-            .cells
-            .par_iter()
-            .map(|cell| match cell.cell_type {
-                CellType::Markdown => (
-                    LanguageType::Markdown,
-                    LanguageType::Markdown.parse_from_str(cell.source.join(""), config),
-                ),
-                CellType::Code => (
-                    language,
-                    language.parse_from_str(cell.source.join(""), config),
-                ),
-            })
-            .collect::<Vec<_>>();
-
-        for (language, stats) in iter {
-            *jupyter_stats.blobs.entry(language).or_default() += &stats;
-            jupyter_stats += &stats;
-        }
-
-        Some(jupyter_stats)
+        Some(
+            jupyter
+                .cells
+                .into_iter()
+                .map(|cell| match cell.cell_type {
+                    CellType::Markdown => (LanguageType::Markdown, cell.source.join("")),
+                    CellType::Code => (language, cell.source.join("")),
+                })
+                .collect(),
+        )
     }
 
     /// The embedded language in LF is declared in a construct that looks like this: `target C;`, `target Python`.
@@ -306,12 +540,105 @@ impl LanguageType {
     }
 }
 
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use std::{fs, path::Path};
 
+    #[test]
+    fn names_ignore_case() {
+        assert_eq!("RUST".parse(), Ok(Rust));
+        assert_eq!("cpp".parse(), Ok(Cpp));
+        assert_eq!("C++".parse(), Ok(Cpp));
+        assert_eq!("js".parse(), Ok(JavaScript));
+        assert_eq!("TypeScript".parse(), Ok(TypeScript));
+    }
+
+    #[test]
+    fn unknown_names_suggest_closest() {
+        let error = "pyhton".parse::<LanguageType>().unwrap_err();
+        assert!(error.contains("did you mean `Python`?"), "{}", error);
+
+        let error = "zzzzzz".parse::<LanguageType>().unwrap_err();
+        assert!(!error.contains("did you mean"), "{}", error);
+    }
+
+    #[test]
+    fn globs_beat_extensions() {
+        let config = Config::default();
+        assert_eq!(LanguageType::from_glob("x.blade.php"), Some(Blade));
+        assert_eq!(LanguageType::from_path("x.blade.php", &config), Some(Blade));
+        assert_eq!(LanguageType::from_path("x.php", &config), Some(Php));
+    }
+
+    #[test]
+    fn globs_match_at_any_depth() {
+        for path in [
+            "Dockerfile.dev",
+            "./Dockerfile.dev",
+            "docker/Dockerfile.dev",
+            "/home/alice/project/docker/containerfile",
+        ] {
+            assert_eq!(LanguageType::from_glob(path), Some(Dockerfile), "{}", path);
+        }
+        assert_eq!(LanguageType::from_glob("Dockerfile.dev/main.rs"), None);
+    }
+
+    #[test]
+    fn markdown_fences_use_aliases() {
+        let stats = Markdown.parse_from_str(
+            "# Example\n\n```golang\nfunc main() {}\n```\n",
+            &Config::default(),
+        );
+
+        assert!(stats.blobs.contains_key(&Go), "{:?}", stats.blobs);
+    }
+
+    #[test]
+    fn markdown_fences_follow_commonmark() {
+        let text = "~~~~ {.py .numberLines}\nx = 1\n```\n~~~~\n\n\
+                    1. Step\n\n   ```rust,ignore\n   fn main() {}\n   ```\n\n\
+                    ````\n```python\ny = 2\n```\n````\n\n\
+                    Some ```inline``` code.\n";
+        let stats = Markdown.parse_from_str(text, &Config::default());
+        assert_stats(&stats, 4, 0, 11);
+        assert_stats(&stats.blobs[&Python], 0, 2, 0);
+        assert_stats(&stats.blobs[&Rust], 0, 1, 0);
+        assert_eq!(stats.blobs.len(), 2, "{:?}", stats.blobs);
+    }
+
+    #[test]
+    fn markdown_front_matter_is_counted() {
+        let text = "---\ntitle: Hello\n# comment\n---\n\n# Heading\n\n---\n";
+        let stats = Markdown.parse_from_str(text, &Config::default());
+        assert_stats(&stats, 2, 0, 4);
+        assert_stats(&stats.blobs[&Yaml], 0, 1, 1);
+
+        let stats = Markdown.parse_from_str("+++\ntitle = \"x\"\n+++\nBody\n", &Config::default());
+        assert_stats(&stats.blobs[&Toml], 0, 1, 0);
+
+        let stats = Markdown.parse_from_str("\n---\nnot: front\n---\n", &Config::default());
+        assert!(stats.blobs.is_empty(), "{:?}", stats.blobs);
+    }
+
     #[test]
     fn rust_allows_nested() {
         assert!(LanguageType::Rust.allows_nested());
@@ -332,13 +659,242 @@ mod tests {
         let sample_notebook =
             fs::read_to_string(Path::new("tests").join("data").join("jupyter.ipynb")).unwrap();
 
-        let stats = LanguageType::Jupyter
-            .parse_jupyter(sample_notebook.as_bytes(), &Config::default())
-            .unwrap();
+        let stats =
+            LanguageType::Jupyter.parse_from_slice(sample_notebook.as_bytes(), &Config::default());
 
         assert_stats(&stats, 115, 528, 333);
     }
 
+    #[test]
+    fn classified_lines_match_stats() {
+        fn count(lines: &[LineClass], kind: LineKind, top_level: bool) -> usize {
+            lines
+                .iter()
+                .filter(|line| line.kind == kind && (!top_level || line.language.is_none()))
+                .count()
+        }
+
+        let config = Config::default();
+        let paths = fs::read_dir(Path::new("tests").join("data"))
+            .unwrap()
+            .chain(fs::read_dir(Path::new("tests").join("embedding")).unwrap())
+            .map(|entry| entry.unwrap().path());
+
+        for path in paths {
+            let Some(language) = LanguageType::from_path(&path, &config) else {
+                continue;
+            };
+            // Notebooks count each cell twice, as both the notebook and the
+            // language of the cell.
+            if language == Jupyter {
+                continue;
+            }
+
+            let text = fs::read(&path).unwrap();
+            let stats = language.parse_from_slice(&text, &config);
+            let summary = stats.summarise();
+            let lines = language.classify_lines(&text, &config);
+
+            let line_count = LineIter::new(b'\n', &text).count();
+            assert!(
+                lines
+                    .windows(2)
+                    .all(|pair| 1 <= pair[0].line && pair[0].line <= pair[1].line),
+                "{}",
+                path.display()
+            );
+            assert!(
+                lines.last().into_iter().all(|line| line.line <= line_count),
+                "{}",
+                path.display()
+            );
+
+            for (top_level, stats) in [(true, &stats), (false, &summary)] {
+                assert_eq!(
+                    count(&lines, LineKind::Code, top_level),
+                    stats.code,
+                    "{}",
+                    path.display()
+                );
+                assert_eq!(
+                    count(&lines, LineKind::Comment, top_level),
+                    stats.comments,
+                    "{}",
+                    path.display()
+                );
+                assert_eq!(
+                    count(&lines, LineKind::Blank, top_level),
+                    stats.blanks,
+                    "{}",
+                    path.display()
+                );
+                assert_eq!(
+                    lines
+                        .iter()
+                        .filter(|line| line.doc && (!top_level || line.language.is_none()))
+                        .count(),
+                    stats.docs,
+                    "{}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn doc_comments_are_still_comments() {
+        let text = "/**\n * A.\n */\nclass A {\n    /* a */\n    /**/\n    // a\n    /** a */\n    String a = \"/**\";\n}\n";
+        let stats = Java.parse_from_str(text, &Config::default());
+        assert_stats(&stats, 0, 3, 7);
+        assert_eq!(stats.docs, 4);
+
+        let text = "//! A.\n\n/// B.\nfn b() {}\n// c\n";
+        let stats = Rust.parse_from_str(text, &Config::default());
+        assert_eq!(stats.docs, 0);
+        assert_eq!(stats.blobs[&Markdown].docs, 2);
+        assert_eq!(stats.summarise().docs, 2);
+        assert_eq!(stats.summarise().comments, 3);
+    }
+
+    #[test]
+    fn comments_before_declarations_are_docs() {
+        let text = "// Package x does y.\npackage x\n\n// helper\n\n// F does z.\n// More.\nfunc F() {\n\t// inner\n\tx := 1\n}\n// trailing\n";
+        let stats = Go.parse_from_str(text, &Config::default());
+        assert_stats(&stats, 2, 4, 6);
+        assert_eq!(stats.docs, 3);
+    }
+
+    #[test]
+    fn mixed_lines_are_still_code() {
+        let config = Config {
+            mixed: Some(true),
+            ..Config::default()
+        };
+        let text = "fn main() {\n    let a = 1; // one\n    let b = \"//\";\n    /* two */ let c = 3;\n    // three\n}\n";
+
+        let stats = Rust.parse_from_str(text, &config);
+        assert_stats(&stats, 0, 5, 1);
+        assert_eq!(stats.mixed, Some(2));
+        assert_eq!(Rust.parse_from_str(text, &Config::default()).mixed, None);
+
+        let paths = fs::read_dir(Path::new("tests").join("data"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path());
+        for path in paths {
+            let Some(language) = LanguageType::from_path(&path, &config) else {
+                continue;
+            };
+
+            let text = fs::read(&path).unwrap();
+            let mut stats = language.parse_from_slice(&text, &config).summarise();
+            let mixed = stats.mixed.take().expect("mixed lines are counted");
+            assert!(mixed <= stats.code, "{}", path.display());
+            assert_eq!(
+                stats,
+                language
+                    .parse_from_slice(&text, &Config::default())
+                    .summarise(),
+                "{}",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn logical_lines_are_statements() {
+        let config = Config {
+            logical_lines: Some(true),
+            ..Config::default()
+        };
+
+        let text = fs::read(Path::new("tests").join("data").join("c.c")).unwrap();
+        assert_eq!(C.parse_from_slice(&text, &Config::default()).logical, None);
+
+        let text = "#define MAX(a, b) \\\n    ((a) > (b) ? (a) : (b))\n\n/* ; { */\nint main(void) {\n    for (int i = 0; i < 3; i++) {\n        puts(\";\"); // ;\n    }\n    return 0;\n}\n";
+        let stats = C.parse_from_str(text, &config);
+        assert_stats(&stats, 1, 8, 1);
+        assert_eq!(stats.logical, Some(7));
+
+        let text = "#!/bin/sh\necho a \\\n  b\nif true; then\n  echo c\nfi\n";
+        assert_eq!(Sh.parse_from_str(text, &config).logical, Some(4));
+
+        assert_eq!(Python.parse_from_str("x = 1\n", &config).logical, None);
+    }
+
+    #[test]
+    fn raw_strings_end_at_their_own_delimiter() {
+        let text = "let s = r##\"\n\"# /* not a comment\n\"##;\n// comment\nlet t = r\"\\\";\n";
+        assert_stats(&Rust.parse_from_str(text, &Config::default()), 0, 4, 1);
+
+        let text = "auto s = R\"x(\n)\" /* not a comment\n)x\";\n// comment\n";
+        assert_stats(&Cpp.parse_from_str(text, &Config::default()), 0, 3, 1);
+
+        let text = "local s = [==[\n]] -- not a comment\n]==]\n-- comment\nlocal t = [[x]]\n";
+        assert_stats(&Lua.parse_from_str(text, &Config::default()), 0, 4, 1);
+
+        let text = "--[==[\nx = ]] y\n]==]\nlocal a = 1\n";
+        assert_stats(&Lua.parse_from_str(text, &Config::default()), 0, 1, 3);
+    }
+
+    #[test]
+    fn heredocs_are_code() {
+        let text = "cat <<-'EOF'\n\t# not a comment\n\tit's fine\n\tEOF\n# comment\ncat <<<word\n";
+        assert_stats(&Sh.parse_from_str(text, &Config::default()), 0, 5, 1);
+
+        let text = "x = <<~EOS\n  # don't\nEOS\n# comment\narr << item\n";
+        assert_stats(&Ruby.parse_from_str(text, &Config::default()), 0, 4, 1);
+
+        let text = "x=$((1 << n))\n# comment\necho \"it's\"\ncat << EOF\n# body\nEOF\n";
+        assert_stats(&Sh.parse_from_str(text, &Config::default()), 0, 5, 1);
+
+        let text = "cat <<EOF\n  EOF but not end\n# inside\n  EOF\nEOF\n# comment\n";
+        assert_stats(&Sh.parse_from_str(text, &Config::default()), 0, 5, 1);
+
+        let text = "<?php\n$a = <<<EOT\n  // text\n  EOT;\n// comment\n";
+        assert_stats(&Php.parse_from_str(text, &Config::default()), 0, 4, 1);
+
+        let stats = Sh.parse_from_str(
+            "psql <<SQL\n-- comment\nSELECT 1;\nSQL\n",
+            &Config::default(),
+        );
+        assert_stats(&stats, 0, 2, 0);
+        assert_stats(&stats.blobs[&Sql], 0, 1, 1);
+    }
+
+    #[test]
+    fn interpolations_nest_strings() {
+        let text = "const a = `${ \"a\" + `b // ${ {x: 1}.x }` }`;\n// comment\nconst b = `\n${ f(\"`\") }\n/* not a comment */\n`;\n";
+        assert_stats(
+            &JavaScript.parse_from_str(text, &Config::default()),
+            0,
+            5,
+            1,
+        );
+
+        let text = "val s = \"${map[\"k\"]} /* not\"\n// comment\n";
+        assert_stats(&Kotlin.parse_from_str(text, &Config::default()), 0, 1, 1);
+
+        let text = "let s = \"\\(x[\"y\"]) /* not\"\nlet t = \"\\\\(\" // comment\n// comment\n";
+        assert_stats(&Swift.parse_from_str(text, &Config::default()), 0, 2, 1);
+
+        let text = "s = f\"{x[\"k\"]} # not\"\nt = f'{{literal}} {y}'\n# comment\n";
+        assert_stats(&Python.parse_from_str(text, &Config::default()), 0, 2, 1);
+    }
+
+    #[test]
+    fn embedding_rules_count_blocks() {
+        let text = "<ul>\n<% items.each do |item| %>\n  <li><%= item %></li>\n<% end %>\n<%\n  # setup\n  x = 1\n%>\n</ul>\n";
+        let stats = RubyHtml.parse_from_str(text, &Config::default());
+        assert_stats(&stats, 0, 7, 0);
+        assert_stats(&stats.blobs[&Ruby], 0, 1, 1);
+
+        let text = "<template>\n  <div/>\n</template>\n<style lang=\"scss\">\n.a { color: red; }\n</style>\n<script>x()</script>\n";
+        let stats = Vue.parse_from_str(text, &Config::default());
+        assert_stats(&stats, 0, 6, 0);
+        assert_stats(&stats.blobs[&Sass], 0, 1, 0);
+        assert_stats(&stats.blobs[&JavaScript], 0, 1, 0);
+    }
+
     #[test]
     fn lf_embedded_language_is_counted() {
         let file_text =
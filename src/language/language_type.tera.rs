@@ -4,14 +4,16 @@ use arbitrary::Arbitrary;
 /// information about the language, such as multi line comments, single line
 /// comments, string literal syntax, whether a given language allows nesting
 /// comments.
-#[derive(Deserialize)]
 #[derive(Arbitrary, Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[non_exhaustive]
 #[allow(clippy::upper_case_acronyms)]
 pub enum LanguageType {
     {% for key, value in languages -%}
-        #[allow(missing_docs)] {% if value.name is defined %} #[serde(alias = "{{value.name}}")] {% else %} #[serde(alias = "{{key}}")] {% endif %} {{key}},
+        #[allow(missing_docs)] {{key}},
     {% endfor %}
+    /// A language defined in the `[languages]` table of the configuration.
+    /// The id is only meaningful for the lifetime of the process.
+    Custom(u16),
 }
 
 impl LanguageType {
@@ -29,14 +31,16 @@ impl LanguageType {
             {% for key, value in languages -%}
                 {{key}} => {% if value.name %}"{{value.name}}"{% else %}"{{key}}"{% endif %},
             {% endfor %}
+            Custom(id) => custom::get(id).map_or("Custom", |l| l.name),
         }
     }
 
-    pub(crate) fn _is_blank(self) -> bool {
+    pub(crate) fn is_blank(self) -> bool {
         match self {
             {% for key, v in languages -%}
                 {{key}} => {{ v.blank | default(value=false) }},
             {% endfor %}
+            Custom(_) => false,
         }
     }
 
@@ -53,6 +57,44 @@ impl LanguageType {
             {% for key, v in languages -%}
                 {{key}} => {{ v.literate | default(value=false) }},
             {% endfor %}
+            Custom(id) => custom::get(id).is_some_and(|l| l.literate),
+        }
+    }
+
+    /// Returns the category of the language, e.g. whether it's a programming
+    /// language or a data format.
+    ///
+    /// ```
+    /// use tokei::{Category, LanguageType};
+    ///
+    /// assert_eq!(LanguageType::Rust.category(), Category::Programming);
+    /// assert_eq!(LanguageType::Json.category(), Category::Data);
+    /// ```
+    pub fn category(self) -> Category {
+        match self {
+            {% for key, v in languages -%}
+                {%- set category = v.category | default(value="programming") %}
+                {{key}} => Category::{{ category | capitalize }},
+            {% endfor %}
+            Custom(id) => custom::get(id).map_or(Category::Programming, |l| l.category),
+        }
+    }
+
+    /// Returns the name of the group from `languages.json` that the language
+    /// is counted in with `--group-by group`, if any.
+    ///
+    /// ```
+    /// use tokei::LanguageType;
+    ///
+    /// assert_eq!(LanguageType::Tsx.group(), Some("JavaScript ecosystem"));
+    /// assert_eq!(LanguageType::Rust.group(), None);
+    /// ```
+    pub fn group(self) -> Option<&'static str> {
+        match self {
+            {% for key, v in languages -%}
+                {{key}} => {% if v.group %}Some("{{v.group}}"){% else %}None{% endif %},
+            {% endfor %}
+            Custom(_) => None,
         }
     }
 
@@ -77,6 +119,7 @@ impl LanguageType {
             {% for key, value in languages -%}
                 {{key}} => &[{% for item in value.line_comment | default(value=[]) %}"{{item}}",{% endfor %}],
             {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.line_comments),
         }
     }
 
@@ -96,9 +139,81 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.multi_line_comments),
+        }
+    }
+
+    /// Returns the single line doc comments of a language, which are also
+    /// counted as comments.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Rust;
+    /// assert_eq!(lang.doc_line_comments(), &["///", "//!"]);
+    /// ```
+    pub fn doc_line_comments(self) -> &'static [&'static str] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[{% for item in value.doc_line_comment | default(value=[]) %}"{{item}}",{% endfor %}],
+            {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.doc_line_comments),
+        }
+    }
+
+    /// Returns the keywords of a language's top-level declarations, whose
+    /// preceding line comments are counted as docs.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Go;
+    /// assert_eq!(lang.doc_declarations(), &["package", "func", "type", "var", "const"]);
+    /// ```
+    pub fn doc_declarations(self) -> &'static [&'static str] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[{% for item in value.doc_declarations | default(value=[]) %}"{{item}}",{% endfor %}],
+            {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.doc_declarations),
+        }
+    }
+
+    /// Returns the multi line doc comments of a language, which are also
+    /// counted as comments.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Java;
+    /// assert_eq!(lang.doc_multi_line_comments(), &[("/**", "*/")]);
+    /// ```
+    pub fn doc_multi_line_comments(self) -> &'static [(&'static str, &'static str)]
+    {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[
+                    {%- for items in value.doc_multi_line_comments | default(value=[]) -%}
+                        ({% for item in items %}"{{item}}",{% endfor %}),
+                    {%- endfor -%}
+                ],
+            {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.doc_multi_line_comments),
         }
     }
 
+    /// Returns the syntax used to count the logical lines of a language, see
+    /// `Config.logical_lines`.
+    pub(crate) fn logical_syntax(self) -> Option<LogicalSyntax> {
+        match self {
+            {% for key, value in languages -%}
+                {%- if value.logical_lines -%}
+                    {{key}} => Some(LogicalSyntax {
+                        terminators: &[{% for item in value.logical_lines.terminators | default(value=[]) %}"{{item}}",{% endfor %}],
+                        blocks: &[{% for item in value.logical_lines.blocks | default(value=[]) %}"{{item}}",{% endfor %}],
+                        directives: &[{% for item in value.logical_lines.directives | default(value=[]) %}"{{item}}",{% endfor %}],
+                        continuation: {% if value.logical_lines.continuation %}Some("{{value.logical_lines.continuation}}"){% else %}None{% endif %},
+                    }),
+                {% endif -%}
+            {% endfor %}
+            Custom(id) => custom::get(id).and_then(|l| l.logical_syntax),
+            _ => None,
+        }
+    }
 
     /// Returns whether the language allows nested multi line comments.
     /// ```
@@ -111,6 +226,7 @@ impl LanguageType {
             {% for key, v in languages -%}
                 {{key}} => {{ v.nested | default(value=false) }},
             {% endfor %}
+            Custom(id) => custom::get(id).is_some_and(|l| l.allows_nested),
         }
     }
 
@@ -131,6 +247,7 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.nested_comments),
         }
     }
 
@@ -149,6 +266,7 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.quotes),
         }
     }
 
@@ -167,6 +285,123 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.verbatim_quotes),
+        }
+    }
+
+    /// Returns the templates of the raw quotes of a language, whose closing
+    /// quote depends on the opening one.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Lua;
+    /// assert_eq!(lang.raw_quotes(), &[("[{=}[", "]{=}]")]);
+    /// ```
+    pub fn raw_quotes(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[
+                    {%- for items in value.raw_quotes | default(value=[]) -%}
+                        ({% for item in items %}"{{item}}",{% endfor %}),
+                    {%- endfor -%}
+                ],
+            {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.raw_quotes),
+        }
+    }
+
+    /// Returns the templates of the multi line comments of a language, whose
+    /// end depends on their start.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Lua;
+    /// assert_eq!(lang.raw_comments(), &[("--[{=}[", "]{=}]")]);
+    /// ```
+    pub fn raw_comments(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[
+                    {%- for items in value.raw_comments | default(value=[]) -%}
+                        ({% for item in items %}"{{item}}",{% endfor %}),
+                    {%- endfor -%}
+                ],
+            {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.raw_comments),
+        }
+    }
+
+    /// Returns the interpolations within the strings of a language, as the
+    /// start of the string along with the start and end of the interpolation.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::JavaScript;
+    /// assert_eq!(lang.interpolations(), &[("`", "${", "}")]);
+    /// ```
+    pub fn interpolations(self) -> &'static [(&'static str, &'static str, &'static str)] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[
+                    {%- for items in value.interpolations | default(value=[]) -%}
+                        ({% for item in items %}"{{item}}",{% endfor %}),
+                    {%- endfor -%}
+                ],
+            {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.interpolations),
+        }
+    }
+
+    /// Returns the rules for the blocks of other languages within a language,
+    /// from the `embeddings` of `languages.json`.
+    pub(crate) fn embedding_rules(self) -> &'static [EmbeddingRule] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[
+                    {%- for rule in value.embedding_rules | default(value=[]) -%}
+                        EmbeddingRule {
+                            end: r#"{{rule.end}}"#,
+                            opening: r#"{{rule.opening}}"#,
+                            language: {{rule.language}},
+                            select: Select::{{rule.select}},
+                            delimiter: LineKind::{{rule.delimiter}},
+                            skip_inline: {{rule.skip_inline}},
+                        },
+                    {%- endfor -%}
+                ],
+            {% endfor %}
+            Custom(_) => &[],
+        }
+    }
+
+    /// Returns the start and end of the blocks of `embedding_rules`.
+    pub(crate) fn embedding_contexts(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[
+                    {%- for rule in value.embedding_rules | default(value=[]) -%}
+                        (r#"{{rule.start}}"#, r#"{{rule.end}}"#),
+                    {%- endfor -%}
+                ],
+            {% endfor %}
+            Custom(_) => &[],
+        }
+    }
+
+    /// Returns the operators that open a heredoc in a language, which are
+    /// followed by the tag that ends it.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Php;
+    /// assert_eq!(lang.heredocs(), &["<<<"]);
+    /// ```
+    pub fn heredocs(self) -> &'static [&'static str] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[
+                    {%- for item in value.heredocs | default(value=[]) -%}
+                        "{{item}}",
+                    {%- endfor -%}
+                ],
+            {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.heredocs),
         }
     }
 
@@ -185,6 +420,7 @@ impl LanguageType {
                     {%- endfor %}
                 ],
             {%- endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.doc_quotes),
         }
     }
 
@@ -199,6 +435,61 @@ impl LanguageType {
             {% for key, lang in languages -%}
                 {{key}} => &[{% for item in lang.shebangs | default(value=[]) %}"{{item}}",{% endfor %}],
             {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.shebangs),
+        }
+    }
+
+    pub(crate) fn extensions(self) -> &'static [&'static str] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[{% for item in value.extensions | default(value=[]) %}"{{item}}",{% endfor %}],
+            {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.extensions),
+        }
+    }
+
+    pub(crate) fn filenames(self) -> &'static [&'static str] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[{% for item in value.filenames | default(value=[]) %}"{{item}}",{% endfor %}],
+            {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.filenames),
+        }
+    }
+
+    pub(crate) fn globs(self) -> &'static [&'static str] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[{% for item in value.globs | default(value=[]) %}"{{item}}",{% endfor %}],
+            {% endfor %}
+            Custom(_) => &[],
+        }
+    }
+
+    pub(crate) fn env(self) -> &'static [&'static str] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[{% for item in value.env | default(value=[]) %}"{{item}}",{% endfor %}],
+            {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.env),
+        }
+    }
+
+    pub(crate) fn aliases(self) -> &'static [&'static str] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[{% for item in value.aliases | default(value=[]) %}"{{item}}",{% endfor %}],
+            {% endfor %}
+            Custom(_) => &[],
+        }
+    }
+
+    pub(crate) fn mime(self) -> &'static [&'static str] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[{% for item in value.mime | default(value=[]) %}"{{item}}",{% endfor %}],
+            {% endfor %}
+            Custom(_) => &[],
         }
     }
 
@@ -213,6 +504,7 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.any_multi_line_comments),
         }
     }
 
@@ -232,6 +524,7 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.any_comments),
         }
     }
 
@@ -245,22 +538,25 @@ impl LanguageType {
                 {%- set starting_multi_line_comments = value.multi_line_comments | default(value=[]) | map(attribute="0") -%}
                 {%- set starting_nested_comments = value.nested_comments | default(value=[]) | map(attribute="0") -%}
                 {%- set important_syntax = value.important_syntax | default(value=[]) -%}
+                {%- set raw_quote_syntax = value.raw_quote_syntax | default(value=[]) -%}
+                {%- set heredocs = value.heredocs | default(value=[]) -%}
 
                 {{key}} => &[
                     {%- for item in starting_quotes |
                                    concat(with=starting_doc_quotes) |
                                    concat(with=starting_multi_line_comments) |
                                    concat(with=starting_nested_comments) |
-                                   concat(with=important_syntax) -%}
+                                   concat(with=important_syntax) |
+                                   concat(with=raw_quote_syntax) |
+                                   concat(with=heredocs) -%}
                         "{{item}}",
                     {%- endfor -%}
-                    {%- for context in value.contexts | default(value=[]) -%}
-                        {% if value.kind == "html" %}
-                            "<{{context.tag}}",
-                        {% endif %}
+                    {%- for rule in value.embedding_rules | default(value=[]) -%}
+                        r#"{{rule.start}}"#,
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(id) => custom::get(id).map_or(&[], |l| l.important_syntax),
         }
     }
 
@@ -273,11 +569,15 @@ impl LanguageType {
     ///
     /// assert_eq!(rust, Some(LanguageType::Rust));
     /// ```
-    pub fn from_path<P: AsRef<Path>>(entry: P, _config: &Config)
+    pub fn from_path<P: AsRef<Path>>(entry: P, config: &Config)
         -> Option<Self>
     {
         let entry = entry.as_ref();
 
+        if let Some(language) = config.language_from_path(entry) {
+            return Some(language);
+        }
+
         if let Some(filename) = fsutils::get_filename(entry) {
             match &*filename {
                 {% for key, value in languages -%}
@@ -290,24 +590,62 @@ impl LanguageType {
                 {%- endfor %}
                 _ => ()
             }
+        }
 
-            {% for key, value in languages -%}
-                {%- if value.path_suffixes -%}
-                    {%- for suffix in value.path_suffixes %}
-            if filename.ends_with("{{suffix}}") {
-                return Some({{key}});
-            }
-                    {%- endfor %}
-                {% endif -%}
-            {%- endfor %}
+        if let Some(language) = LanguageType::from_glob(entry) {
+            return Some(language);
         }
 
         match fsutils::get_extension(entry) {
-            Some(extension) => LanguageType::from_file_extension(extension.as_str()),
-            None => LanguageType::from_shebang(entry),
+            Some(extension) => LanguageType::from_heuristics(entry, &extension)
+                .or_else(|| LanguageType::from_file_extension(extension.as_str()))
+                .or_else(|| LanguageType::from_modeline(entry)),
+            None => custom::from_shebang(entry, config)
+                .or_else(|| LanguageType::from_shebang(entry))
+                .or_else(|| LanguageType::from_modeline(entry)),
         }
     }
 
+    /// Get language from the `globs` of `languages.json`, which are matched
+    /// against the whole path, ignoring case. When several globs match, the
+    /// first language in `languages.json` is used.
+    ///
+    /// ```
+    /// use tokei::LanguageType;
+    ///
+    /// let dockerfile = LanguageType::from_glob("./docker/Dockerfile.dev");
+    ///
+    /// assert_eq!(dockerfile, Some(LanguageType::Dockerfile));
+    /// ```
+    pub fn from_glob<P: AsRef<Path>>(entry: P) -> Option<Self> {
+        static GLOBS: Lazy<(globset::GlobSet, Vec<LanguageType>)> = Lazy::new(|| {
+            let globs: &[(LanguageType, &str)] = &[
+                {% for key, value in languages -%}
+                    {%- for glob in value.globs | default(value=[]) -%}
+                        ({{key}}, "{{glob}}"),
+                    {% endfor -%}
+                {%- endfor %}
+            ];
+
+            let mut builder = globset::GlobSetBuilder::new();
+            for (_, pattern) in globs {
+                builder.add(glob::glob(pattern, true).expect("Globs are validated by build.rs"));
+            }
+
+            (
+                builder.build().expect("Globs are validated by build.rs"),
+                globs.iter().map(|(language, _)| *language).collect(),
+            )
+        });
+
+        let (globs, languages) = &*GLOBS;
+        globs
+            .matches(entry)
+            .into_iter()
+            .min()
+            .map(|i| languages[i])
+    }
+
     /// Get language from a file extension.
     ///
     /// ```no_run
@@ -319,30 +657,103 @@ impl LanguageType {
     /// ```
     #[must_use]
     pub fn from_file_extension(extension: &str) -> Option<Self> {
+        Self::from_known_extension(extension).or_else(|| {
+            warn!("Unknown extension: {}", extension);
+            None
+        })
+    }
+
+    /// Like `from_file_extension`, without warning about unknown extensions.
+    pub(crate) fn from_known_extension(extension: &str) -> Option<Self> {
         match extension {
             {% for key, value in languages -%}
-                {%- if value.extensions -%}
-                    {%- for item in value.extensions  %}| "{{item}}" {% endfor %}=> Some({{key}}),
+                {%- if value.unique_extensions -%}
+                    {%- for item in value.unique_extensions  %}| "{{item}}" {% endfor %}=> Some({{key}}),
                 {% endif -%}
             {%- endfor %}
-            extension => {
-                warn!("Unknown extension: {}", extension);
-                None
-            },
+            _ => None,
         }
     }
 
-    /// Get language from its name.
+    /// Get language from a name, alias or extension ignoring case, such as a
+    /// word written in a file to select the language of a block of code.
+    /// Unlike `from_name`, unknown words are quietly ignored.
+    pub(crate) fn from_selector(selector: &str) -> Option<Self> {
+        let lowercase = selector.to_lowercase();
+        Self::from_alias(&lowercase)
+            .or_else(|| custom::from_name(selector))
+            .or_else(|| Self::from_known_extension(&lowercase))
+    }
+
+    /// Get language from the start of a file with an extension shared by
+    /// several languages, using the `heuristics` of `languages.json`. Returns
+    /// `None` when the extension has no heuristics, or none of them match.
+    /// May open and read the file.
     ///
     /// ```no_run
     /// use tokei::LanguageType;
     ///
-    /// let rust = LanguageType::from_name("Rust");
+    /// let prolog = LanguageType::from_heuristics("./family.pl", "pl");
     ///
-    /// assert_eq!(rust, Some(LanguageType::Rust));
+    /// assert_eq!(prolog, Some(LanguageType::Prolog));
+    /// ```
+    pub fn from_heuristics<P: AsRef<Path>>(entry: P, extension: &str) -> Option<Self> {
+        // The rules are only matched against the start of the file, which
+        // is plenty to find the first declarations in it.
+        const READ_LIMIT: u64 = 8 * 1024;
+
+        type Rules = Vec<(LanguageType, regex::bytes::Regex)>;
+        static HEURISTICS: Lazy<std::collections::HashMap<&'static str, Rules>> = Lazy::new(|| {
+            [
+                {% for extension, rules in heuristics -%}
+                    ("{{extension}}", vec![
+                        {% for rule in rules -%}
+                            ({{rule.language}}, regex::bytes::Regex::new(r#"(?m){{rule.pattern}}"#).unwrap()),
+                        {% endfor %}
+                    ]),
+                {% endfor %}
+            ]
+            .into_iter()
+            .collect()
+        });
+
+        let rules = HEURISTICS.get(extension)?;
+        let mut header = Vec::new();
+        File::open(entry)
+            .ok()?
+            .take(READ_LIMIT)
+            .read_to_end(&mut header)
+            .ok()?;
+
+        rules
+            .iter()
+            .find(|(_, pattern)| pattern.is_match(&header))
+            .map(|(language, _)| *language)
+    }
+
+    /// Get language from its name, or one of its aliases, ignoring case.
+    ///
+    /// ```
+    /// use tokei::LanguageType;
+    ///
+    /// assert_eq!(LanguageType::from_name("Rust"), Some(LanguageType::Rust));
+    /// assert_eq!(LanguageType::from_name("c++"), Some(LanguageType::Cpp));
+    /// assert_eq!(LanguageType::from_name("golang"), Some(LanguageType::Go));
     /// ```
     #[must_use]
     pub fn from_name(name: &str) -> Option<Self> {
+        name.parse()
+            .map_err(|error| warn!("{}", error))
+            .ok()
+    }
+
+    /// Get one of the languages from `languages.json` from its name or one of
+    /// its aliases, ignoring case like `FromStr`.
+    pub(crate) fn from_builtin_name(name: &str) -> Option<Self> {
+        Self::from_exact_name(name).or_else(|| Self::from_alias(&name.to_lowercase()))
+    }
+
+    fn from_exact_name(name: &str) -> Option<Self> {
         match name {
             {% for key, value in languages -%}
                 {% if value.name and value.name != key -%}
@@ -350,10 +761,27 @@ impl LanguageType {
                 {% endif -%}
                     | "{{key}}" => Some({{key}}),
             {% endfor %}
-            unknown => {
-                warn!("Unknown language name: {}", unknown);
-                None
-            },
+            _ => None,
+        }
+    }
+
+    /// Every lowercase name and alias of the languages.
+    pub(crate) const ALIASES: &'static [(&'static str, Self)] = &[
+        {% for key, value in languages -%}
+            {%- for item in value.unique_aliases | default(value=[]) %}("{{item}}", {{key}}), {% endfor %}
+        {% endfor -%}
+    ];
+
+    /// Get language from a lowercase name, or one of the `aliases` of
+    /// `languages.json`, such as the file types of Vim and Emacs modelines.
+    pub(crate) fn from_alias(alias: &str) -> Option<Self> {
+        match alias {
+            {% for key, value in languages -%}
+                {%- if value.unique_aliases -%}
+                    {%- for item in value.unique_aliases  %}| "{{item}}" {% endfor %}=> Some({{key}}),
+                {% endif -%}
+            {%- endfor %}
+            _ => None,
         }
     }
 
@@ -391,72 +819,57 @@ impl LanguageType {
     /// assert_eq!(rust, Some(LanguageType::Rust));
     /// ```
     pub fn from_shebang<P: AsRef<Path>>(entry: P) -> Option<Self> {
-        // Read at max `READ_LIMIT` bytes from the given file.
-        // A typical shebang line has a length less than 32 characters;
-        // e.g. '#!/bin/bash' - 11B / `#!/usr/bin/env python3` - 22B
-        // It is *very* unlikely the file contains a valid shebang syntax
-        // if we don't find a newline character after searching the first 128B.
-        const READ_LIMIT: usize = 128;
-
-        let mut file = File::open(entry).ok()?;
-        let mut buf = [0; READ_LIMIT];
-
-        let len = file.read(&mut buf).ok()?;
-        let buf = &buf[..len];
-
-        let first_line = buf.split(|b| *b == b'\n').next()?;
-        let first_line = std::str::from_utf8(first_line).ok()?;
-
-        let mut words = first_line.split_whitespace();
-        match words.next() {
-            {# First match against any shebang paths, and then check if the
-               language matches any found in the environment shebang path. #}
+        let first_line = shebang::read_first_line(entry.as_ref())?;
+        let interpreter = shebang::interpreter(&first_line)?;
+
+        // Versioned interpreters such as `python3.11` are only stripped when
+        // they aren't a language of their own, such as `perl6`.
+        LanguageType::from_interpreter(interpreter)
+            .or_else(|| LanguageType::from_interpreter(shebang::strip_version(interpreter)))
+            .or_else(|| {
+                warn!("Unknown interpreter: {:?}", interpreter);
+                None
+            })
+    }
+
+    /// Get language from the name of the program run by a shebang, which is
+    /// either one of the `shebangs` or `env` of `languages.json`.
+    pub(crate) fn from_interpreter(interpreter: &str) -> Option<Self> {
+        match interpreter {
             {% for key, value in languages -%}
-                {%- if value.shebangs %}
-                    {%- for item in value.shebangs  %}| Some("{{item}}") {% endfor %}=> Some({{key}}),
+                {%- if value.unique_interpreters -%}
+                    {%- for item in value.unique_interpreters  %}| "{{item}}" {% endfor %}=> Some({{key}}),
                 {% endif -%}
             {%- endfor %}
-
-            Some("#!/usr/bin/env") => {
-                if let Some(word) = words.next() {
-                    match word {
-                        {% for key, value in languages -%}
-                            {%- if value.env -%}
-                                {%- for item in value.env  %}
-                                    {% if loop.index == 1 %}
-                                        _ if word.starts_with("{{item}}")
-                                    {% else %}
-                                        || word.starts_with("{{item}}")
-                                    {% endif %}
-                                {% endfor %}=> Some({{key}}),
-                            {% endif -%}
-                        {%- endfor %}
-                        env => {
-                            warn!("Unknown environment: {:?}", env);
-                            None
-                        }
-                    }
-                } else {
-                    None
-                }
-            }
             _ => None,
         }
     }
+
+    /// Get language from a Vim or Emacs modeline, such as `# vim: ft=python`
+    /// or `# -*- mode: ruby -*-`, in the first or last few lines of a file.
+    /// May open and read the file.
+    ///
+    /// ```no_run
+    /// use tokei::LanguageType;
+    ///
+    /// let python = LanguageType::from_modeline("./configure");
+    ///
+    /// assert_eq!(python, Some(LanguageType::Python));
+    /// ```
+    pub fn from_modeline<P: AsRef<Path>>(entry: P) -> Option<Self> {
+        modeline::from_path(entry.as_ref())
+    }
 }
 
 impl FromStr for LanguageType {
-    type Err = &'static str;
+    type Err = String;
 
+    /// Parses a language from its name, or one of its aliases, ignoring case.
+    /// The error suggests the closest name when there's a near match.
     fn from_str(from: &str) -> Result<Self, Self::Err> {
-        match &*from.to_lowercase() {
-            {% for key, value in languages %}
-                {% if value.name %}"{{value.name | lower}}"{% else %}"{{key | lower}}"{% endif %}
-                => Ok({{key}}),
-            {% endfor %}
-            _ => Err("Language not found, please use `-l` to see all available\
-                     languages."),
-        }
+        Self::from_alias(&from.to_lowercase())
+            .or_else(|| custom::from_name(from))
+            .ok_or_else(|| Self::unknown_name_error(from))
     }
 }
 
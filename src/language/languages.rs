@@ -8,8 +8,10 @@ use std::{
 use rayon::prelude::*;
 
 use crate::{
+    category::Category,
     config::Config,
-    language::{Language, LanguageType},
+    language::{Language, LanguageGroup, LanguageType},
+    stats::{add_optional, Skipped},
     utils,
 };
 
@@ -20,6 +22,7 @@ use crate::{
 #[derive(Debug, Default, PartialEq)]
 pub struct Languages {
     inner: BTreeMap<LanguageType, Language>,
+    skipped: Vec<Skipped>,
 }
 
 impl serde::Serialize for Languages {
@@ -82,10 +85,22 @@ impl Languages {
         ignored: &[&str],
         config: &Config,
     ) {
-        utils::fs::get_all_files(paths, ignored, &mut self.inner, config);
+        let mut skipped = utils::fs::get_all_files(paths, ignored, &mut self.inner, config);
+        skipped.sort();
+        self.skipped.extend(skipped);
         self.inner.par_iter_mut().for_each(|(_, l)| l.total());
     }
 
+    /// The files that were matched to a language but skipped without being
+    /// counted, such as binary files, sorted by path within each call to
+    /// [`Languages::get_statistics`].
+    ///
+    /// [`Languages::get_statistics`]: struct.Languages.html#method.get_statistics
+    #[must_use]
+    pub fn skipped(&self) -> &[Skipped] {
+        &self.skipped
+    }
+
     /// Constructs a new, Languages struct. Languages is always empty and does
     /// not allocate.
     ///
@@ -101,17 +116,92 @@ impl Languages {
     /// Summary of the Languages struct.
     #[must_use]
     pub fn total(self: &Languages) -> Language {
+        self.total_of(|_| true)
+    }
+
+    /// Summary of the Languages struct, leaving out languages outside of the
+    /// [`Category::Programming`] category when `config` has
+    /// `total_programming_only` set.
+    ///
+    /// ```
+    /// use tokei::{Config, Language, LanguageType, Languages};
+    ///
+    /// let mut languages = Languages::new();
+    /// let mut yaml = Language::new();
+    /// yaml.code = 10;
+    /// languages.insert(LanguageType::Yaml, yaml);
+    ///
+    /// let config = Config {
+    ///     total_programming_only: Some(true),
+    ///     ..Config::default()
+    /// };
+    ///
+    /// assert_eq!(languages.total().code, 10);
+    /// assert_eq!(languages.total_for(&config).code, 0);
+    /// ```
+    ///
+    /// [`Category::Programming`]: enum.Category.html#variant.Programming
+    #[must_use]
+    pub fn total_for(&self, config: &Config) -> Language {
+        let programming_only = config.total_programming_only.unwrap_or(false);
+        self.total_of(|ty| !programming_only || ty.category() == Category::Programming)
+    }
+
+    fn total_of(&self, include: impl Fn(LanguageType) -> bool) -> Language {
         let mut total = Language::new();
         for (ty, l) in self {
+            if !include(*ty) {
+                continue;
+            }
+
             let language = l.summarise();
             total.comments += language.comments;
+            total.docs += language.docs;
             total.blanks += language.blanks;
             total.code += language.code;
+            add_optional(&mut total.mixed, language.mixed);
+            add_optional(&mut total.logical, language.logical);
             total.inaccurate |= language.inaccurate;
             total.children.insert(*ty, language.reports.clone());
         }
         total
     }
+
+    /// Folds the languages into rows for each group of languages, from
+    /// `languages.json` or `Config.groups`. Languages that aren't in a group
+    /// get a row of their own, named after the language.
+    ///
+    /// ```
+    /// use tokei::{Config, Language, LanguageType, Languages};
+    ///
+    /// let mut languages = Languages::new();
+    /// languages.insert(LanguageType::C, Language::new());
+    /// languages.insert(LanguageType::Cpp, Language::new());
+    /// languages.insert(LanguageType::Rust, Language::new());
+    ///
+    /// let groups = languages.groups(&Config::default());
+    ///
+    /// assert_eq!(groups.len(), 2);
+    /// assert_eq!(groups["C family"].languages.len(), 2);
+    /// assert!(groups.contains_key("Rust"));
+    /// ```
+    #[must_use]
+    pub fn groups(&self, config: &Config) -> BTreeMap<String, LanguageGroup> {
+        let mut groups: BTreeMap<String, LanguageGroup> = BTreeMap::new();
+
+        for (language_type, language) in self {
+            let name = config
+                .group_of(*language_type)
+                .unwrap_or_else(|| language_type.name());
+
+            groups
+                .entry(name.to_owned())
+                .or_default()
+                .add_language(*language_type, language.clone());
+        }
+
+        groups
+    }
 }
 
 impl IntoIterator for Languages {
@@ -1,13 +1,28 @@
+pub(crate) mod binary;
+mod classify;
+pub(crate) mod custom;
 mod embedding;
+mod encoding;
+mod group;
+mod language_syntax;
 pub mod language_type;
 pub mod languages;
+mod modeline;
+mod raw_template;
+mod shebang;
 mod syntax;
+pub(crate) mod vendored;
 
-use std::{collections::BTreeMap, mem, ops::AddAssign};
+use std::{cmp::Reverse, collections::BTreeMap, mem, ops::AddAssign};
 
-pub use self::{language_type::*, languages::Languages};
+pub use self::{
+    group::LanguageGroup, language_syntax::LanguageSyntax, language_type::*, languages::Languages,
+};
 
-use crate::{sort::Sort, stats::Report};
+use crate::{
+    sort::Sort,
+    stats::{add_optional, Report},
+};
 
 /// A struct representing statistics about a single Language.
 #[derive(Clone, Debug, Deserialize, Default, PartialEq, Serialize)]
@@ -18,6 +33,18 @@ pub struct Language {
     pub code: usize,
     /// The total number of comments(both single, and multi-line)
     pub comments: usize,
+    /// The total number of comments that are documentation, which are also
+    /// counted in `comments`.
+    #[serde(default)]
+    pub docs: usize,
+    /// The total number of lines of code that also contain a comment, when
+    /// enabled with `Config.mixed`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mixed: Option<usize>,
+    /// The total number of logical lines, when enabled with
+    /// `Config.logical_lines`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logical: Option<usize>,
     /// A collection of statistics of individual files.
     pub reports: Vec<Report>,
     /// A map of any languages found in the reports.
@@ -75,8 +102,11 @@ impl Language {
         for reports in self.children.values() {
             for stats in reports.iter().map(|r| r.stats.summarise()) {
                 summary.comments += stats.comments;
+                summary.docs += stats.docs;
                 summary.code += stats.code;
                 summary.blanks += stats.blanks;
+                add_optional(&mut summary.mixed, stats.mixed);
+                add_optional(&mut summary.logical, stats.logical);
             }
         }
 
@@ -104,16 +134,25 @@ impl Language {
         let mut blanks = 0;
         let mut code = 0;
         let mut comments = 0;
+        let mut docs = 0;
+        let mut mixed = None;
+        let mut logical = None;
 
         for report in &self.reports {
             blanks += report.stats.blanks;
             code += report.stats.code;
             comments += report.stats.comments;
+            docs += report.stats.docs;
+            add_optional(&mut mixed, report.stats.mixed);
+            add_optional(&mut logical, report.stats.logical);
         }
 
         self.blanks = blanks;
         self.code = code;
         self.comments = comments;
+        self.docs = docs;
+        self.mixed = mixed;
+        self.logical = logical;
     }
 
     /// Checks if the language is empty. Empty meaning it doesn't have any
@@ -149,17 +188,11 @@ impl Language {
     /// ```
     pub fn sort_by(&mut self, category: Sort) {
         match category {
-            Sort::Blanks => self
-                .reports
-                .sort_by(|a, b| b.stats.blanks.cmp(&a.stats.blanks)),
-            Sort::Comments => self
-                .reports
-                .sort_by(|a, b| b.stats.comments.cmp(&a.stats.comments)),
-            Sort::Code => self.reports.sort_by(|a, b| b.stats.code.cmp(&a.stats.code)),
+            Sort::Blanks => self.reports.sort_by_key(|r| Reverse(r.stats.blanks)),
+            Sort::Comments => self.reports.sort_by_key(|r| Reverse(r.stats.comments)),
+            Sort::Code => self.reports.sort_by_key(|r| Reverse(r.stats.code)),
             Sort::Files => self.reports.sort_by(|a, b| a.name.cmp(&b.name)),
-            Sort::Lines => self
-                .reports
-                .sort_by(|a, b| b.stats.lines().cmp(&a.stats.lines())),
+            Sort::Lines => self.reports.sort_by_key(|r| Reverse(r.stats.lines())),
         }
     }
 }
@@ -167,8 +200,11 @@ impl Language {
 impl AddAssign for Language {
     fn add_assign(&mut self, mut rhs: Self) {
         self.comments += rhs.comments;
+        self.docs += rhs.docs;
         self.blanks += rhs.blanks;
         self.code += rhs.code;
+        add_optional(&mut self.mixed, rhs.mixed);
+        add_optional(&mut self.logical, rhs.logical);
         self.reports.extend(mem::take(&mut rhs.reports));
         self.children.extend(mem::take(&mut rhs.children));
         self.inaccurate |= rhs.inaccurate;
@@ -0,0 +1,127 @@
+//! Detecting the language of a file from Vim and Emacs modelines.
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::language::LanguageType;
+
+/// How many lines are checked at the start and at the end of a file, the
+/// same as Vim's default for its `modelines` option.
+const LINES: usize = 5;
+/// How many bytes are read from the start and from the end of a file.
+const READ_LIMIT: u64 = 4 * 1024;
+
+/// Get language from a modeline in the first or last `LINES` of a file.
+pub(crate) fn from_path(entry: &Path) -> Option<LanguageType> {
+    let mut file = File::open(entry).ok()?;
+    let len = file.metadata().ok()?.len();
+
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    if len <= 2 * READ_LIMIT {
+        file.read_to_end(&mut head).ok()?;
+    } else {
+        Read::by_ref(&mut file)
+            .take(READ_LIMIT)
+            .read_to_end(&mut head)
+            .ok()?;
+        file.seek(SeekFrom::End(-(READ_LIMIT as i64))).ok()?;
+        file.read_to_end(&mut tail).ok()?;
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let tail = String::from_utf8_lossy(&tail);
+    let last_lines = if tail.is_empty() { &head } else { &tail };
+
+    head.lines()
+        .take(LINES)
+        .chain(last_lines.lines().rev().take(LINES))
+        .find_map(from_line)
+}
+
+/// Get language from a single line containing a modeline.
+fn from_line(line: &str) -> Option<LanguageType> {
+    vim_filetype(line)
+        .or_else(|| emacs_mode(line))
+        .and_then(|name| LanguageType::from_alias(&name.to_lowercase()))
+}
+
+/// Finds the `filetype` or `syntax` of a Vim modeline, in either the
+/// `vim: ft=python` or the `vim: set ft=python :` form.
+fn vim_filetype(line: &str) -> Option<&str> {
+    static VIM: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"(?:^|\s)(?:vi|vim|ex)(?:[<=>]?\d+)?:(?:.*?[\s:])?(?:ft|filetype|syn|syntax)=([\w+#.-]+)",
+        )
+        .unwrap()
+    });
+
+    let filetype = VIM.captures(line)?.get(1)?.as_str();
+    // Compound file types such as `htmldjango.html` are matched by the first.
+    filetype.split('.').next()
+}
+
+/// Finds the mode of an Emacs modeline, in either the `-*- ruby -*-` or the
+/// `-*- mode: ruby; coding: utf-8 -*-` form.
+fn emacs_mode(line: &str) -> Option<&str> {
+    static EMACS: Lazy<Regex> = Lazy::new(|| Regex::new(r"-\*-\s*(.*?)\s*-\*-").unwrap());
+
+    let variables = EMACS.captures(line)?.get(1)?.as_str();
+    let mode = if variables.contains(':') {
+        variables.split(';').find_map(|variable| {
+            let (name, value) = variable.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case("mode")
+                .then(|| value.trim())
+        })?
+    } else {
+        variables
+    };
+
+    Some(mode.strip_suffix("-mode").unwrap_or(mode))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vim_modelines() {
+        assert_eq!(from_line("# vim: ft=python"), Some(LanguageType::Python));
+        assert_eq!(
+            from_line("/* vim: set filetype=cpp ts=4 : */"),
+            Some(LanguageType::Cpp)
+        );
+        assert_eq!(from_line("# vim:sw=2:ft=sh"), Some(LanguageType::Sh));
+        assert_eq!(
+            from_line("\" vim: syntax=vim"),
+            Some(LanguageType::VimScript)
+        );
+        assert_eq!(
+            from_line("// vim: ft=javascript.jsx"),
+            Some(LanguageType::JavaScript)
+        );
+        assert_eq!(from_line("fixes for the environment: ft=python"), None);
+    }
+
+    #[test]
+    fn emacs_modelines() {
+        assert_eq!(from_line("# -*- ruby -*-"), Some(LanguageType::Ruby));
+        assert_eq!(
+            from_line("# -*- mode: python; coding: utf-8 -*-"),
+            Some(LanguageType::Python)
+        );
+        assert_eq!(
+            from_line(";; -*- Mode: Emacs-Lisp -*-"),
+            Some(LanguageType::Elisp)
+        );
+        assert_eq!(from_line("// -*- c++-mode -*-"), Some(LanguageType::Cpp));
+        assert_eq!(from_line("# -*- coding: utf-8 -*-"), None);
+    }
+}
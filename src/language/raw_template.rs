@@ -0,0 +1,93 @@
+//! Parsing the templates of raw strings and comments, e.g. `r{#}"` and `"{#}`
+//! for Rust's `r#"…"#`. `build.rs` includes this module too, so that the
+//! templates it accepts in `languages.json` are exactly the ones tokei can
+//! count.
+
+/// A delimiter of a raw string or comment, split around its placeholder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Template<'a> {
+    pub(crate) before: &'a str,
+    /// The character `c` of a `{c}` placeholder, which matches any number of
+    /// it, or `None` for `{}`, which matches any short delimiter.
+    pub(crate) repeat: Option<u8>,
+    pub(crate) after: &'a str,
+}
+
+impl<'a> Template<'a> {
+    fn split(template: &'a str) -> Result<Self, &'static str> {
+        let (before, rest) = template
+            .split_once('{')
+            .ok_or("doesn't have a placeholder")?;
+        let (placeholder, after) = rest
+            .split_once('}')
+            .ok_or("doesn't close its placeholder")?;
+        if after.contains(['{', '}']) {
+            return Err("has more than one placeholder");
+        }
+
+        let repeat = match placeholder.as_bytes() {
+            [] => None,
+            &[c] if c.is_ascii_graphic() => Some(c),
+            _ => return Err("has a placeholder that isn't a single printable ASCII character"),
+        };
+
+        Ok(Self {
+            before,
+            repeat,
+            after,
+        })
+    }
+
+    /// The longest literal part of the delimiter, which is looked for by the
+    /// quick scan for important syntax, as every line with the delimiter
+    /// contains it.
+    pub(crate) fn important_syntax(&self) -> &'a str {
+        if self.before.len() > self.after.len() {
+            self.before
+        } else {
+            self.after
+        }
+    }
+}
+
+/// Parses the `start` and `end` templates of a raw string or comment. Both
+/// need the same placeholder exactly once, and `start` some text besides it.
+pub(crate) fn parse<'a>(
+    start: &'a str,
+    end: &'a str,
+) -> Result<(Template<'a>, Template<'a>), &'static str> {
+    let start = Template::split(start)?;
+    let end = Template::split(end)?;
+
+    if start.repeat != end.repeat {
+        Err("has different placeholders in its start and end")
+    } else if start.before.is_empty() && start.after.is_empty() {
+        Err("has nothing besides the placeholder in its start")
+    } else {
+        Ok((start, end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates() {
+        let (start, end) = parse("r{#}\"", "\"{#}").unwrap();
+        assert_eq!(
+            (start.before, start.repeat, start.after),
+            ("r", Some(b'#'), "\"")
+        );
+        assert_eq!(end.before, "\"");
+        assert_eq!(start.important_syntax(), "\"");
+        assert_eq!(parse("R\"{}(", "){}\"").unwrap().0.repeat, None);
+
+        assert!(parse("r\"", "\"").is_err());
+        assert!(parse("r{#}\"", "\"{=}").is_err());
+        assert!(parse("r{##}\"", "\"{##}").is_err());
+        assert!(parse("r{ }\"", "\"{ }").is_err());
+        assert!(parse("r{#}{#}\"", "\"{#}").is_err());
+        assert!(parse("{#}", "{#}").is_err());
+    }
+}
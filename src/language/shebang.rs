@@ -0,0 +1,109 @@
+//! Parsing the interpreter out of shebang lines.
+
+use std::{fs::File, io::Read, path::Path};
+
+/// How many bytes are read when looking for a shebang. A typical shebang
+/// line has a length less than 32 characters, e.g. `#!/bin/bash`, but paths
+/// such as `#!/nix/store/<hash>-bash-5.2/bin/bash` can be a lot longer.
+const READ_LIMIT: usize = 256;
+
+/// Reads the first line of a file, if it fits in `READ_LIMIT`.
+pub(crate) fn read_first_line(entry: &Path) -> Option<String> {
+    let mut file = File::open(entry).ok()?;
+    let mut buf = [0; READ_LIMIT];
+
+    let len = file.read(&mut buf).ok()?;
+    let first_line = buf[..len].split(|b| *b == b'\n').next()?;
+
+    std::str::from_utf8(first_line)
+        .ok()
+        .map(|line| line.trim_end().to_owned())
+}
+
+/// Returns the name of the program run by a shebang line, looking through
+/// `env` and its options, e.g. `python3` for `#!/usr/bin/env -S python3 -u`.
+pub(crate) fn interpreter(line: &str) -> Option<&str> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let program = basename(words.next()?);
+
+    if program != "env" {
+        return Some(program);
+    }
+
+    loop {
+        let word = words.next()?;
+        match word {
+            // Options taking a separate argument.
+            "-u" | "--unset" | "-C" | "--chdir" | "-P" => {
+                words.next();
+            }
+            "--" => return words.next().map(basename),
+            _ => {
+                if let Some(command) = word
+                    .strip_prefix("--split-string=")
+                    .or_else(|| word.strip_prefix("-S").filter(|c| !c.is_empty()))
+                {
+                    return Some(basename(command));
+                } else if !word.starts_with('-') && !word.contains('=') {
+                    return Some(basename(word));
+                }
+                // Any other option, e.g. `-S` or `-i`, or an environment
+                // variable assignment.
+            }
+        }
+    }
+}
+
+/// Strips a version from the end of an interpreter's name, e.g. `python3.11`
+/// becomes `python`, and `guile-3.0` becomes `guile`.
+pub(crate) fn strip_version(name: &str) -> &str {
+    let stripped = name
+        .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+        .trim_end_matches(['-', '_']);
+
+    if stripped.is_empty() || stripped.len() == name.len() {
+        name
+    } else {
+        stripped
+    }
+}
+
+fn basename(path: &str) -> &str {
+    let path = path.trim_matches(|c| c == '"' || c == '\'');
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpreters() {
+        assert_eq!(interpreter("#!/bin/bash"), Some("bash"));
+        assert_eq!(interpreter("#! /bin/sh -e"), Some("sh"));
+        assert_eq!(interpreter("#!/usr/bin/python3.11"), Some("python3.11"));
+        assert_eq!(interpreter("#!/usr/bin/env ruby"), Some("ruby"));
+        assert_eq!(interpreter("#!/usr/bin/env -S python3 -u"), Some("python3"));
+        assert_eq!(interpreter("#!/usr/bin/env -Sperl -w"), Some("perl"));
+        assert_eq!(interpreter("#!/usr/bin/env node --harmony"), Some("node"));
+        assert_eq!(
+            interpreter("#!/usr/bin/env -i -u HOME LANG=C -- /usr/local/bin/lua"),
+            Some("lua")
+        );
+        assert_eq!(
+            interpreter("#!/nix/store/q3ms7yw2gs0r4kbv2yjbi7dvzm3pslpc-bash-5.2p26/bin/bash"),
+            Some("bash")
+        );
+        assert_eq!(interpreter("#!/usr/bin/env"), None);
+        assert_eq!(interpreter("// not a shebang"), None);
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(strip_version("python3.11"), "python");
+        assert_eq!(strip_version("python3"), "python");
+        assert_eq!(strip_version("guile-3.0"), "guile");
+        assert_eq!(strip_version("bash"), "bash");
+        assert_eq!(strip_version("7"), "7");
+    }
+}
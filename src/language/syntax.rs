@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{borrow::Cow, sync::Arc};
 
 use aho_corasick::AhoCorasick;
 use dashmap::DashMap;
@@ -6,12 +6,11 @@ use grep_searcher::LineStep;
 use log::Level::Trace;
 use once_cell::sync::Lazy;
 
-use super::embedding::{
-    RegexCache, RegexFamily, ENDING_LF_BLOCK_REGEX, ENDING_MARKDOWN_REGEX, END_SCRIPT, END_STYLE,
-    END_TEMPLATE,
-};
+use super::classify::LineSink;
+use super::embedding::{Embedding, Fence, RegexCache, RegexFamily, ENDING_LF_BLOCK_REGEX};
+use super::raw_template::{self, Template};
 use crate::LanguageType::LinguaFranca;
-use crate::{stats::CodeStats, utils::ext::SliceExt, Config, LanguageType};
+use crate::{stats::LineKind, utils::ext::SliceExt, LanguageType};
 
 /// Tracks the syntax of the language as well as the current state in the file.
 /// Current has what could be consider three types of mode.
@@ -24,42 +23,333 @@ use crate::{stats::CodeStats, utils::ext::SliceExt, Config, LanguageType};
 /// - `comment` mode: This when the state machine is current inside a comment
 ///   for a given language, strings cannot trigger `string` mode while in
 ///   `comment` mode.
+///
+/// An interpolation within a string, e.g. `${` in JavaScript, goes back to
+/// parsing code until its end, and then returns to the string it's in.
 #[derive(Clone, Debug)]
 pub(crate) struct SyntaxCounter {
     pub(crate) shared: Arc<SharedMatchers>,
-    pub(crate) quote: Option<&'static str>,
+    pub(crate) quote: Option<Cow<'static, str>>,
     pub(crate) quote_is_doc_quote: bool,
-    pub(crate) stack: Vec<&'static str>,
+    pub(crate) stack: Vec<Cow<'static, str>>,
     pub(crate) quote_is_verbatim: bool,
+    /// The start and end of interpolations within the current string.
+    pub(crate) quote_interpolation: Option<(&'static str, &'static str)>,
+    /// The strings that have been left for the code of an interpolation,
+    /// innermost last.
+    pub(crate) interpolations: Vec<Interpolation>,
     pub(crate) lf_embedded_language: Option<LanguageType>,
+    /// Whether the outermost multi line comment in `stack` is a doc comment.
+    pub(crate) doc_comment: bool,
+    /// Whether lines of code that also contain a comment are counted with
+    /// `LineSink::push_mixed`, see `Config.mixed`.
+    pub(crate) count_mixed: bool,
+    /// Whether logical lines are counted with `LineSink::push_logical`, see
+    /// `Config.logical_lines`.
+    pub(crate) count_logical: bool,
+    /// Whether the previous line was a directive, e.g. `#define`, that
+    /// continues onto this line.
+    pub(crate) in_directive: bool,
+    /// Whether the text being parsed starts at the start of the file, where
+    /// Markdown can have front matter.
+    pub(crate) starts_file: bool,
+    /// The heredocs opened so far whose bodies haven't ended, in the order
+    /// that their bodies follow the line that opened them.
+    pub(crate) heredocs: Vec<Heredoc>,
 }
 
+/// A string that's been left for the code of an interpolation within it,
+/// which is returned to at the end of the interpolation.
 #[derive(Clone, Debug)]
-pub(crate) struct FileContext {
+pub(crate) struct Interpolation {
+    quote: Cow<'static, str>,
+    quote_is_doc_quote: bool,
+    quote_is_verbatim: bool,
+    /// The start and end of the interpolation, e.g. `${` and `}`.
+    delimiters: (&'static str, &'static str),
+    /// The number of brackets opened within the interpolation that have yet
+    /// to be closed, e.g. the `{` of an object literal within `${`.
+    depth: usize,
+}
+
+/// A heredoc, e.g. `<<EOF`, whose body continues until a line made of its
+/// terminator.
+#[derive(Clone, Debug)]
+pub(crate) struct Heredoc {
+    /// The tag of the heredoc, e.g. `EOF`, without any quotes.
+    pub terminator: String,
+    /// The language that the tag names, e.g. `SQL`, whose syntax the body is
+    /// counted with.
+    pub language: Option<LanguageType>,
+    /// How the terminator is written on the line that ends the body.
+    closing: Closing,
+}
+
+/// How the terminator of a heredoc is written on the line that ends its body,
+/// which depends on the operator that opened it.
+#[derive(Clone, Copy, Debug)]
+enum Closing {
+    /// The whole line, e.g. after `<<`.
+    Exact,
+    /// The rest of the line after tabs, e.g. after `<<-`.
+    AfterTabs,
+    /// The rest of the line after any indentation, e.g. after `<<~`.
+    AfterIndent,
+    /// The start of the line after any indentation, followed by code, e.g.
+    /// `EOT;` after PHP's `<<<`.
+    BeforeCode,
+}
+
+impl Heredoc {
+    fn is_tag(b: &u8) -> bool {
+        b.is_ascii_alphanumeric() || *b == b'_'
+    }
+
+    /// If `window` starts with one of the `openers` of heredocs followed by a
+    /// tag, e.g. `<<-'EOF'`, returns the heredoc along with the length of its
+    /// opening. `before` is the text of the line before `window`, where the
+    /// opener can't continue a longer operator, e.g. Bash's herestring
+    /// `<<<word`, or be within shell arithmetic, e.g. `$((1 << n))`. An
+    /// opener that ends in a space, e.g. `<< `, has to follow a command word.
+    fn open(window: &[u8], before: &[u8], openers: &[&str]) -> Option<(usize, Self)> {
+        let previous = before.last();
+        if count(before, b"((") > count(before, b"))") {
+            return None;
+        }
+
+        openers.iter().find_map(|opener| {
+            if previous.is_some() && previous == opener.as_bytes().first() {
+                return None;
+            }
+
+            if opener.ends_with(' ') && !Self::follows_command(before) {
+                return None;
+            }
+
+            let rest = window.strip_prefix(opener.as_bytes())?;
+            let (quote, rest) = match rest.first() {
+                Some(&q @ (b'\'' | b'"')) => (Some(q), &rest[1..]),
+                Some(b'\\') => (None, &rest[1..]),
+                _ => (None, rest),
+            };
+            let length = rest.iter().take_while(|b| Self::is_tag(b)).count();
+            if length == 0 || rest[0].is_ascii_digit() {
+                return None;
+            }
+
+            let after = &rest[length..];
+            let closed = match quote {
+                Some(q) if after.first() == Some(&q) => 1,
+                Some(_) => return None,
+                None => 0,
+            };
+            let terminator = String::from_utf8_lossy(&rest[..length]).into_owned();
+            let language = LanguageType::from_alias(&terminator.to_lowercase());
+            let closing = match opener.trim_end() {
+                "<<<" => Closing::BeforeCode,
+                opener if opener.ends_with('-') => Closing::AfterTabs,
+                opener if opener.ends_with('~') => Closing::AfterIndent,
+                _ => Closing::Exact,
+            };
+            let opening = window.len() - after.len() + closed;
+            Some((
+                opening,
+                Self {
+                    terminator,
+                    language,
+                    closing,
+                },
+            ))
+        })
+    }
+
+    /// Whether `before` ends with a word such as `cat `, rather than a number
+    /// or an operator.
+    fn follows_command(before: &[u8]) -> bool {
+        let before = before.trim();
+        let length = before.iter().rev().take_while(|b| Self::is_tag(b)).count();
+        let word = &before[before.len() - length..];
+        word.first().is_some_and(|b| !b.is_ascii_digit())
+    }
+
+    /// Whether the untrimmed `line` ends the body of the heredoc.
+    pub(crate) fn is_terminator(&self, line: &[u8]) -> bool {
+        let line = line
+            .strip_suffix(b"\n")
+            .map_or(line, |line| line.strip_suffix(b"\r").unwrap_or(line));
+        let terminator = self.terminator.as_bytes();
+        match self.closing {
+            Closing::Exact => line == terminator,
+            Closing::AfterTabs => {
+                let tabs = line.iter().take_while(|&&b| b == b'\t').count();
+                &line[tabs..] == terminator
+            }
+            Closing::AfterIndent => line.trim_start() == terminator,
+            Closing::BeforeCode => line
+                .trim_start()
+                .strip_prefix(terminator)
+                .is_some_and(|rest| !rest.first().is_some_and(Self::is_tag)),
+        }
+    }
+}
+
+/// The number of times `needle` occurs in `haystack`.
+fn count(haystack: &[u8], needle: &[u8]) -> usize {
+    haystack
+        .windows(needle.len())
+        .filter(|w| *w == needle)
+        .count()
+}
+
+/// The syntax of a language's statements, used to count its logical lines,
+/// from the `logical_lines` property in `languages.json`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct LogicalSyntax {
+    /// Tokens that end a statement, e.g. `;`. Each line of code is a
+    /// statement when there are none, such as in shell scripts.
+    pub terminators: &'static [&'static str],
+    /// Tokens that open a block, e.g. `{`, each counted as a statement.
+    pub blocks: &'static [&'static str],
+    /// Prefixes of lines that are a single statement, e.g. `#` for the
+    /// directives of the C preprocessor.
+    pub directives: &'static [&'static str],
+    /// The end of a line that continues the statement onto the next line,
+    /// e.g. `\`.
+    pub continuation: Option<&'static str>,
+}
+
+impl LogicalSyntax {
+    /// Whether `window` starts with a statement terminator or block opener.
+    fn is_statement(&self, window: &[u8]) -> bool {
+        self.terminators
+            .iter()
+            .chain(self.blocks)
+            .any(|token| window.starts_with(token.as_bytes()))
+    }
+
+    /// The number of statements in `line` before any of the `line_comments`,
+    /// for lines without any strings or multi line comments.
+    fn count(&self, line: &[u8], line_comments: &[&str]) -> usize {
+        let mut statements = 0;
+        for i in 0..line.len() {
+            let window = &line[i..];
+            if line_comments
+                .iter()
+                .any(|c| window.starts_with(c.as_bytes()))
+            {
+                break;
+            } else if self.is_statement(window) {
+                statements += 1;
+            }
+        }
+        statements
+    }
+}
+
+/// A string literal whose closing delimiter depends on its opening one, from
+/// the `raw_quotes` property in `languages.json`. The varying part of both
+/// delimiters is marked with a placeholder, either `{c}` for any number of a
+/// character `c`, e.g. `r{#}"` and `"{#}` for Rust's `r#"…"#`, or `{}` for any
+/// short delimiter, e.g. `R"{}(` and `){}"` for C++'s `R"x(…)x"`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RawQuote {
+    start: Template<'static>,
+    end: Template<'static>,
+}
+
+impl RawQuote {
+    /// The longest delimiter that `{}` matches, as in C++.
+    const MAX_DELIMITER: usize = 16;
+
+    /// Parses the `start` and `end` templates, which are validated by
+    /// `build.rs` and [`custom::register`](super::custom::register).
+    pub(crate) fn new(start: &'static str, end: &'static str) -> Self {
+        let (start, end) = raw_template::parse(start, end)
+            .unwrap_or_else(|error| panic!("The raw template {start:?} {error}."));
+        Self { start, end }
+    }
+
+    /// If `window` starts with the opening delimiter, returns its length
+    /// along with the closing delimiter.
+    fn open(&self, window: &[u8]) -> Option<(usize, String)> {
+        let Template { before, after, .. } = self.start;
+        let rest = window.strip_prefix(before.as_bytes())?;
+        let captured = match self.start.repeat {
+            Some(c) => rest.iter().take_while(|&&b| b == c).count(),
+            None => rest
+                .iter()
+                .take(Self::MAX_DELIMITER + 1)
+                .take_while(|&&b| {
+                    b.is_ascii_graphic() && b != b'\\' && Some(&b) != after.as_bytes().first()
+                })
+                .count(),
+        };
+
+        if captured > Self::MAX_DELIMITER && self.start.repeat.is_none()
+            || !rest[captured..].starts_with(after.as_bytes())
+        {
+            return None;
+        }
+
+        let delimiter = String::from_utf8_lossy(&rest[..captured]);
+        let end = format!("{}{}{}", self.end.before, delimiter, self.end.after);
+        Some((before.len() + captured + after.len(), end))
+    }
+}
+
+/// A block of another language found within a file, which is counted by the
+/// caller of [`SyntaxCounter::perform_multi_line_analysis`].
+#[derive(Clone, Debug)]
+pub(crate) struct FileContext<'a> {
     pub(crate) language: LanguageContext,
-    pub(crate) stats: CodeStats,
+    /// The text of the block, without its delimiters.
+    pub(crate) text: Cow<'a, [u8]>,
+    /// Where the first line of `text` is in the file.
+    pub(crate) start: usize,
     pub(crate) end: usize,
 }
 
-impl FileContext {
-    pub fn new(language: LanguageContext, end: usize, stats: CodeStats) -> Self {
+impl<'a> FileContext<'a> {
+    pub fn new(
+        language: LanguageContext,
+        start: usize,
+        end: usize,
+        text: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
         Self {
             language,
-            stats,
+            text: text.into(),
+            start,
             end,
         }
     }
+
+    /// A block whose `text` is a slice of `lines`, the text of the file.
+    fn borrowed(language: LanguageContext, lines: &'a [u8], end: usize, text: &'a [u8]) -> Self {
+        let start = if text.is_empty() {
+            end
+        } else {
+            text.as_ptr() as usize - lines.as_ptr() as usize
+        };
+
+        Self::new(language, start, end, text)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub(crate) enum LanguageContext {
-    Html {
+    /// A block declared by the `embeddings` of `languages.json`.
+    Embedded {
         language: LanguageType,
+        /// How the line that the block starts on is counted.
+        delimiter: LineKind,
     },
     LinguaFranca,
     Markdown {
         balanced: bool,
-        language: LanguageType,
+        /// The language of the code, which is `None` for a code block whose
+        /// info string names no known language.
+        language: Option<LanguageType>,
     },
     Rust,
 }
@@ -75,17 +365,26 @@ pub(crate) struct SharedMatchers {
     pub is_fortran: bool,
     pub is_literate: bool,
     pub line_comments: &'static [&'static str],
+    pub doc_line_comments: &'static [&'static str],
+    pub doc_declarations: &'static [&'static str],
+    pub doc_multi_line_comments: &'static [(&'static str, &'static str)],
+    pub logical_syntax: Option<LogicalSyntax>,
     pub any_multi_line_comments: &'static [(&'static str, &'static str)],
     pub multi_line_comments: &'static [(&'static str, &'static str)],
     pub nested_comments: &'static [(&'static str, &'static str)],
     pub string_literals: &'static [(&'static str, &'static str)],
     pub verbatim_string_literals: &'static [(&'static str, &'static str)],
+    pub raw_string_literals: Vec<RawQuote>,
+    pub raw_comments: Vec<RawQuote>,
+    pub heredocs: &'static [&'static str],
+    pub interpolations: &'static [(&'static str, &'static str, &'static str)],
+    pub embeddings: Vec<Embedding>,
 }
 
+static MATCHERS: Lazy<DashMap<LanguageType, Arc<SharedMatchers>>> = Lazy::new(DashMap::new);
+
 impl SharedMatchers {
     pub fn new(language: LanguageType) -> Arc<Self> {
-        static MATCHERS: Lazy<DashMap<LanguageType, Arc<SharedMatchers>>> = Lazy::new(DashMap::new);
-
         MATCHERS
             .entry(language)
             .or_insert_with(|| Arc::new(Self::init(language)))
@@ -93,6 +392,11 @@ impl SharedMatchers {
             .clone()
     }
 
+    /// Drops the cached matchers of a language whose syntax has changed.
+    pub fn invalidate(language: LanguageType) {
+        MATCHERS.remove(&language);
+    }
+
     pub fn init(language: LanguageType) -> Self {
         fn init_corasick(pattern: &[&'static str]) -> AhoCorasick {
             AhoCorasick::builder()
@@ -113,21 +417,49 @@ impl SharedMatchers {
             important_syntax: init_corasick(language.important_syntax()),
             any_comments: language.any_comments(),
             line_comments: language.line_comments(),
+            doc_line_comments: language.doc_line_comments(),
+            doc_declarations: language.doc_declarations(),
+            doc_multi_line_comments: language.doc_multi_line_comments(),
+            logical_syntax: language.logical_syntax(),
             multi_line_comments: language.multi_line_comments(),
             any_multi_line_comments: language.any_multi_line_comments(),
             nested_comments: language.nested_comments(),
             string_literals: language.quotes(),
             verbatim_string_literals: language.verbatim_quotes(),
+            raw_string_literals: language
+                .raw_quotes()
+                .iter()
+                .map(|&(start, end)| RawQuote::new(start, end))
+                .collect(),
+            raw_comments: language
+                .raw_comments()
+                .iter()
+                .map(|&(start, end)| RawQuote::new(start, end))
+                .collect(),
+            heredocs: language.heredocs(),
+            interpolations: language.interpolations(),
+            embeddings: language
+                .embedding_rules()
+                .iter()
+                .map(Embedding::new)
+                .collect(),
         }
     }
 }
 
 #[derive(Debug)]
-pub(crate) enum AnalysisReport {
-    /// No child languages were found, contains a boolean representing whether
-    /// the line ended with comments or not.
-    Normal(bool),
-    ChildLanguage(FileContext),
+pub(crate) enum AnalysisReport<'a> {
+    /// No child languages were found.
+    Normal {
+        /// Whether the line ended with comments.
+        ended_with_comments: bool,
+        /// Whether a comment started or ended anywhere in the line.
+        has_comments: bool,
+        /// The number of statement terminators and block openers outside of
+        /// strings and comments, when counting logical lines.
+        statements: usize,
+    },
+    ChildLanguage(FileContext<'a>),
 }
 
 impl SyntaxCounter {
@@ -136,15 +468,24 @@ impl SyntaxCounter {
             shared: SharedMatchers::new(language),
             quote_is_doc_quote: false,
             quote_is_verbatim: false,
+            quote_interpolation: None,
+            interpolations: Vec::new(),
             stack: Vec::with_capacity(1),
             lf_embedded_language: None,
+            doc_comment: false,
+            count_mixed: false,
+            count_logical: false,
+            in_directive: false,
+            starts_file: true,
+            heredocs: Vec::new(),
             quote: None,
         }
     }
 
-    /// Returns whether the syntax is currently in plain mode.
+    /// Returns whether the syntax is currently in plain mode, outside of any
+    /// interpolation.
     pub(crate) fn is_plain_mode(&self) -> bool {
-        self.quote.is_none() && self.stack.is_empty()
+        self.quote.is_none() && self.stack.is_empty() && self.interpolations.is_empty()
     }
 
     /// Returns whether the syntax is currently in string mode.
@@ -180,18 +521,101 @@ impl SyntaxCounter {
         }
     }
 
+    /// Returns whether `line` is a line comment that could document the
+    /// declaration after it, e.g. Go's `// F does z.` before `func F()`.
+    pub(crate) fn is_doc_candidate(&self, line: &[u8]) -> bool {
+        !self.shared.doc_declarations.is_empty()
+            && self.is_plain_mode()
+            && !self.starts_with_doc_comment(line)
+            && self
+                .shared
+                .line_comments
+                .iter()
+                .any(|c| line.starts_with(c.as_bytes()))
+    }
+
+    /// Returns whether the untrimmed `line` starts a top-level declaration,
+    /// so that the line comments before it are its docs.
+    pub(crate) fn is_documented_declaration(&self, line: &[u8]) -> bool {
+        self.shared.doc_declarations.iter().any(|keyword| {
+            line.strip_prefix(keyword.as_bytes())
+                .and_then(|rest| rest.first())
+                .is_some_and(|&c| c.is_ascii_whitespace() || c == b'(')
+        })
+    }
+
+    /// Returns whether `window` starts with a doc comment, e.g. `///` or `/**`,
+    /// rather than an empty comment such as `/**/`.
+    pub(crate) fn starts_with_doc_comment(&self, window: &[u8]) -> bool {
+        self.shared
+            .doc_line_comments
+            .iter()
+            .any(|c| window.starts_with(c.as_bytes()))
+            || self
+                .shared
+                .doc_multi_line_comments
+                .iter()
+                .any(|(start, _)| window.starts_with(start.as_bytes()))
+                && !self
+                    .shared
+                    .any_multi_line_comments
+                    .iter()
+                    .any(|(start, end)| {
+                        window.starts_with(start.as_bytes())
+                            && window[start.len()..].starts_with(end.as_bytes())
+                    })
+    }
+
+    /// Adds the logical lines that end on a line of code, with the
+    /// `statements` found by `perform_multi_line_analysis`, or by looking for
+    /// them in the line when it has no strings or multi line comments.
+    pub(crate) fn push_logical_lines(
+        &mut self,
+        line: &[u8],
+        statements: Option<usize>,
+        number: usize,
+        stats: &mut impl LineSink,
+    ) {
+        let logical = match self.shared.logical_syntax {
+            Some(logical) if self.count_logical => logical,
+            _ => return,
+        };
+        let continued = logical
+            .continuation
+            .is_some_and(|c| line.trim().ends_with(c.as_bytes()));
+
+        let count = if self.in_directive
+            || logical
+                .directives
+                .iter()
+                .any(|d| line.starts_with(d.as_bytes()))
+        {
+            self.in_directive = continued;
+            usize::from(!continued)
+        } else if logical.terminators.is_empty() {
+            usize::from(!continued)
+        } else {
+            statements.unwrap_or_else(|| logical.count(line, self.shared.line_comments))
+        };
+
+        if count != 0 {
+            stats.push_logical(number, count);
+        }
+    }
+
     /// Try to see if we can determine what a line is from examining the whole
     /// line at once. Returns `true` if successful.
     pub(crate) fn try_perform_single_line_analysis(
-        &self,
+        &mut self,
         line: &[u8],
-        stats: &mut crate::stats::CodeStats,
+        number: usize,
+        stats: &mut impl LineSink,
     ) -> bool {
         if !self.is_plain_mode() {
             false
         } else if line.trim().is_empty() {
-            stats.blanks += 1;
-            trace!("Blank No.{}", stats.blanks);
+            stats.push(number, LineKind::Blank);
+            trace!("Blank");
             true
         } else if self.shared.important_syntax.is_match(line) {
             false
@@ -205,25 +629,44 @@ impl SyntaxCounter {
                     .iter()
                     .any(|c| line.starts_with(c.as_bytes()))
             {
-                stats.comments += 1;
-                trace!("Comment No.{}", stats.comments);
+                if !self.shared.is_literate && self.starts_with_doc_comment(line) {
+                    stats.push_doc(number);
+                    trace!("Doc");
+                } else {
+                    stats.push(number, LineKind::Comment);
+                    trace!("Comment");
+                }
+            } else if self.count_mixed
+                && self
+                    .shared
+                    .line_comments
+                    .iter()
+                    .any(|c| line.contains_slice(c.as_bytes()))
+            {
+                // Without any important syntax there are no strings that the
+                // comment could be inside of.
+                stats.push_mixed(number);
+                trace!("Mixed");
+                self.push_logical_lines(line, None, number, stats);
             } else {
-                stats.code += 1;
-                trace!("Code No.{}", stats.code);
+                stats.push(number, LineKind::Code);
+                trace!("Code");
+                self.push_logical_lines(line, None, number, stats);
             }
 
             true
         }
     }
 
-    pub(crate) fn perform_multi_line_analysis(
+    pub(crate) fn perform_multi_line_analysis<'a>(
         &mut self,
-        lines: &[u8],
+        lines: &'a [u8],
         start: usize,
         end: usize,
-        config: &Config,
-    ) -> AnalysisReport {
+    ) -> AnalysisReport<'a> {
         let mut ended_with_comments = false;
+        let mut has_comments = false;
+        let mut statements = 0;
         let mut skip = 0;
         macro_rules! skip {
             ($skip:expr) => {{
@@ -231,7 +674,13 @@ impl SyntaxCounter {
             }};
         }
 
-        let regex_cache = RegexCache::build(self.shared.language, lines, start, end);
+        let regex_cache = RegexCache::build(
+            self.shared.language,
+            &self.shared.embeddings,
+            lines,
+            start,
+            end,
+        );
 
         for i in start..end {
             if skip != 0 {
@@ -246,9 +695,16 @@ impl SyntaxCounter {
             }
 
             ended_with_comments = false;
-            let is_end_of_quote_or_multi_line = self
-                .parse_end_of_quote(window)
-                .or_else(|| self.parse_end_of_multi_line(window));
+            if let Some(skip_amount) = self.parse_interpolation(window) {
+                skip!(skip_amount);
+                continue;
+            }
+
+            let is_end_of_quote_or_multi_line = self.parse_end_of_quote(window).or_else(|| {
+                let end_of_multi_line = self.parse_end_of_multi_line(window);
+                has_comments |= end_of_multi_line.is_some();
+                end_of_multi_line
+            });
 
             if let Some(skip_amount) = is_end_of_quote_or_multi_line {
                 ended_with_comments = true;
@@ -258,13 +714,25 @@ impl SyntaxCounter {
                 continue;
             }
 
-            if let Some(child) = self.parse_context(lines, i, end, config, &regex_cache) {
+            if let Some(skip_amount) = self.parse_end_of_interpolation(window) {
+                skip!(skip_amount);
+                continue;
+            }
+
+            if let Some(child) = self.parse_context(lines, i, end, &regex_cache) {
                 return AnalysisReport::ChildLanguage(child);
             }
 
-            let is_quote_or_multi_line = self
-                .parse_quote(window)
-                .or_else(|| self.parse_multi_line_comment(window));
+            if let Some(skip_amount) = self.parse_heredoc(window, &lines[start..i]) {
+                skip!(skip_amount);
+                continue;
+            }
+
+            let is_quote_or_multi_line = self.parse_quote(window).or_else(|| {
+                let multi_line = self.parse_multi_line_comment(window);
+                has_comments |= multi_line.is_some();
+                multi_line
+            });
 
             if let Some(skip_amount) = is_quote_or_multi_line {
                 skip!(skip_amount);
@@ -273,11 +741,26 @@ impl SyntaxCounter {
 
             if self.parse_line_comment(window) {
                 ended_with_comments = true;
+                has_comments = true;
                 break;
             }
+
+            if self.count_logical
+                && self.is_plain_mode()
+                && self
+                    .shared
+                    .logical_syntax
+                    .is_some_and(|logical| logical.is_statement(window))
+            {
+                statements += 1;
+            }
         }
 
-        AnalysisReport::Normal(ended_with_comments)
+        AnalysisReport::Normal {
+            ended_with_comments,
+            has_comments,
+            statements,
+        }
     }
 
     /// Performs a set of heuristics to determine whether a line is a comment or
@@ -289,9 +772,9 @@ impl SyntaxCounter {
     ///  1. Check if string literal is a doc string and whether tokei has
     ///     been configured to treat them as comments.
     ///     - Yes: When the line starts with the doc string or when we are
-    ///            continuing from a previous line.
+    ///       continuing from a previous line.
     ///  - No: The string is a normal string literal or tokei isn't
-    ///        configured to count them as comments.
+    ///    configured to count them as comments.
     /// 2. If we're not in string mode, check if we left it this on this line.
     ///    - Yes: When we found a doc quote and we started in comments.
     /// 3. Yes: When the whole line is a comment e.g. `/* hello */`
@@ -318,6 +801,10 @@ impl SyntaxCounter {
                     .any(|(start, end)| {
                         trimmed.starts_with(start.as_bytes()) && trimmed.ends_with(end.as_bytes())
                     })
+                || self.shared.raw_comments.iter().any(|raw| {
+                    raw.open(trimmed)
+                        .is_some_and(|(_, end)| trimmed.ends_with(end.as_bytes()))
+                })
         };
         let starts_with_comment = || {
             let quote = match self.stack.last() {
@@ -329,13 +816,20 @@ impl SyntaxCounter {
                 .any_multi_line_comments
                 .iter()
                 .any(|(start, end)| end == quote && trimmed.starts_with(start.as_bytes()))
+                || self
+                    .shared
+                    .raw_comments
+                    .iter()
+                    .any(|raw| raw.open(trimmed).is_some_and(|(_, end)| end == **quote))
         };
 
         // `Some(true)` in order to respect the current configuration.
         #[allow(clippy::if_same_then_else)]
         if self.quote.is_some() {
             if self.quote_is_doc_quote && config.treat_doc_strings_as_comments == Some(true) {
-                self.quote.map_or(false, |q| line.starts_with(q.as_bytes()))
+                self.quote
+                    .as_ref()
+                    .is_some_and(|q| line.starts_with(q.as_bytes()))
                     || (self.quote.is_some())
             } else {
                 false
@@ -358,55 +852,70 @@ impl SyntaxCounter {
     }
 
     #[inline]
-    pub(crate) fn parse_context(
+    pub(crate) fn parse_context<'a>(
         &mut self,
-        lines: &[u8],
+        lines: &'a [u8],
         start: usize,
         end: usize,
-        config: &Config,
         regex_cache: &RegexCache,
-    ) -> Option<FileContext> {
-        use std::str::FromStr;
-
+    ) -> Option<FileContext<'a>> {
         // static TYPE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"type="(.*)".*>"#).unwrap());
         if self.quote.is_some() || !self.stack.is_empty() {
             return None;
         }
 
         match regex_cache.family()? {
-            RegexFamily::Markdown(md) => {
-                if !lines[start..end].contains_slice(b"```") {
-                    return None;
+            RegexFamily::Markdown => {
+                if let Some(front_matter) = self.parse_front_matter(lines, start) {
+                    return Some(front_matter);
                 }
 
-                let opening_fence = md.starts_in_range(start, end)?;
-                let start_of_code = opening_fence.end();
-                let closing_fence = ENDING_MARKDOWN_REGEX.find(&lines[start_of_code..]);
-                if let Some(m) = &closing_fence {
-                    trace!("{:?}", String::from_utf8_lossy(m.as_bytes()));
+                if !matches!(lines[start], b'`' | b'~') {
+                    return None;
                 }
-                let end_of_code = closing_fence
-                    .map_or_else(|| lines.len(), |fence| start_of_code + fence.start());
-                let end_of_code_block =
-                    closing_fence.map_or_else(|| lines.len(), |fence| start_of_code + fence.end());
-                let balanced = closing_fence.is_some();
-                let identifier = &opening_fence.as_bytes().trim()[3..];
-
-                let language = identifier
-                    .split(|&b| b == b',')
-                    .find_map(|s| LanguageType::from_str(&String::from_utf8_lossy(s)).ok())?;
-                trace!(
-                    "{} BLOCK: {:?}",
-                    language,
-                    String::from_utf8_lossy(&lines[start_of_code..end_of_code])
-                );
-                let stats =
-                    language.parse_from_slice(lines[start_of_code..end_of_code].trim(), config);
 
-                Some(FileContext::new(
-                    LanguageContext::Markdown { balanced, language },
-                    end_of_code_block,
-                    stats,
+                let start_of_line = lines[..start]
+                    .iter()
+                    .rposition(|&b| b == b'\n')
+                    .map_or(0, |i| i + 1);
+                let end_of_line = lines[start..end]
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(end, |i| start + i);
+                let fence = Fence::open(&lines[start_of_line..start], &lines[start..end_of_line])?;
+                trace!("{:?}", fence);
+
+                // The block runs to the end of the file when it isn't closed,
+                // and a shorter fence or one of the other character is part
+                // of the code.
+                let start_of_code = (end_of_line + 1).min(lines.len());
+                let mut stepper = LineStep::new(b'\n', start_of_code, lines.len());
+                let mut closing = None;
+                while let Some((start, end)) = stepper.next(lines) {
+                    if fence.is_closed_by(&lines[start..end]) {
+                        closing = Some((start, end));
+                        break;
+                    }
+                }
+                let (end_of_code, end_of_block) = closing.unwrap_or((lines.len(), lines.len()));
+                let code = &lines[start_of_code..end_of_code];
+
+                let language = fence.language();
+                trace!("{:?} BLOCK: {:?}", language, String::from_utf8_lossy(code));
+                Some(FileContext::borrowed(
+                    LanguageContext::Markdown {
+                        balanced: closing.is_some(),
+                        language,
+                    },
+                    lines,
+                    end_of_block,
+                    // Code in an unknown language is counted line by line
+                    // like the rest of the file.
+                    if language.is_some() {
+                        code.trim()
+                    } else {
+                        code
+                    },
                 ))
             }
             RegexFamily::Rust => {
@@ -421,6 +930,7 @@ impl SyntaxCounter {
 
                 let mut stepper = LineStep::new(b'\n', start, lines.len());
                 let mut markdown = Vec::new();
+                let mut starts_of_lines = Vec::new();
                 let mut end_of_block = lines.len();
 
                 while let Some((start, end)) = stepper.next(lines) {
@@ -429,6 +939,7 @@ impl SyntaxCounter {
                         let line = lines[start..end].trim_start();
                         let stripped_line = &line[3.min(line.len())..];
                         markdown.extend_from_slice(stripped_line);
+                        starts_of_lines.push(start);
                         end_of_block = end;
                     } else {
                         end_of_block = start;
@@ -437,12 +948,19 @@ impl SyntaxCounter {
                 }
 
                 trace!("Markdown found: {:?}", String::from_utf8_lossy(&markdown));
-                let doc_block = LanguageType::Markdown.parse_from_slice(markdown.trim(), config);
-
+                let text = markdown.trim();
+                let blank_lines = markdown[..markdown.len() - markdown.trim_start().len()]
+                    .iter()
+                    .filter(|&&b| b == b'\n')
+                    .count();
                 Some(FileContext::new(
                     LanguageContext::Rust,
+                    starts_of_lines
+                        .get(blank_lines)
+                        .copied()
+                        .unwrap_or(end_of_block),
                     end_of_block,
-                    doc_block,
+                    text.to_vec(),
                 ))
             }
             RegexFamily::LinguaFranca(lf) => {
@@ -454,102 +972,81 @@ impl SyntaxCounter {
 
                 let block_contents = &lines[start_of_code..end_of_code];
                 trace!("LF block: {:?}", String::from_utf8_lossy(block_contents));
-                let stats = self.get_lf_target_language().parse_from_slice(
+                Some(FileContext::borrowed(
+                    LanguageContext::LinguaFranca,
+                    lines,
+                    end_of_code,
                     block_contents.trim_first_and_last_line_of_whitespace(),
-                    config,
-                );
-                trace!("-> stats: {:?}", stats);
+                ))
+            }
+            RegexFamily::Embedded(embedded) => {
+                let opening = embedded.opening_at(start)?;
+                let embedding = &self.shared.embeddings[opening.embedding];
+                let start_of_code = opening.capture.end();
+                let closing = embedding.end.find(&lines[start_of_code..])?;
+                let end_of_code = start_of_code + closing.start();
+                let contents = &lines[start_of_code..end_of_code];
+                if contents.trim().is_empty() {
+                    return None;
+                }
 
-                Some(FileContext::new(
-                    LanguageContext::LinguaFranca,
+                let rule = embedding.rule;
+                if rule.skip_inline && !contents.contains(&b'\n') {
+                    return None;
+                }
+
+                let language = rule.language(opening.selector.as_ref().map(|s| s.as_bytes()));
+                trace!(
+                    "{} BLOCK: {:?}",
+                    language,
+                    String::from_utf8_lossy(contents)
+                );
+                Some(FileContext::borrowed(
+                    LanguageContext::Embedded {
+                        language,
+                        delimiter: rule.delimiter,
+                    },
+                    lines,
                     end_of_code,
-                    stats,
+                    contents.trim_first_and_last_line_of_whitespace(),
                 ))
             }
-            RegexFamily::HtmlLike(html) => {
-                if let Some(mut captures) = html.start_script_in_range(start, end) {
-                    let start_of_code = captures.next().unwrap().end();
-                    let closing_tag = END_SCRIPT.find(&lines[start_of_code..])?;
-                    let end_of_code = start_of_code + closing_tag.start();
-                    let language = captures
-                        .next()
-                        .and_then(|m| {
-                            LanguageType::from_mime(&String::from_utf8_lossy(m.as_bytes().trim()))
-                        })
-                        .unwrap_or(LanguageType::JavaScript);
-                    let script_contents = &lines[start_of_code..end_of_code];
-                    if script_contents.trim().is_empty() {
-                        return None;
-                    }
+        }
+    }
 
-                    let stats = language.parse_from_slice(
-                        script_contents.trim_first_and_last_line_of_whitespace(),
-                        config,
-                    );
-                    Some(FileContext::new(
-                        LanguageContext::Html { language },
-                        end_of_code,
-                        stats,
-                    ))
-                } else if let Some(mut captures) = html.start_style_in_range(start, end) {
-                    let start_of_code = captures.next().unwrap().end();
-                    let closing_tag = END_STYLE.find(&lines[start_of_code..])?;
-                    let end_of_code = start_of_code + closing_tag.start();
-                    let language = captures
-                        .next()
-                        .and_then(|m| {
-                            LanguageType::from_str(
-                                &String::from_utf8_lossy(m.as_bytes().trim()).to_lowercase(),
-                            )
-                            .ok()
-                        })
-                        .unwrap_or(LanguageType::Css);
-                    let style_contents = &lines[start_of_code..end_of_code];
-                    if style_contents.trim().is_empty() {
-                        return None;
-                    }
+    /// Front matter at the start of a Markdown file, i.e. YAML between `---`
+    /// lines or TOML between `+++` lines, which is counted like a code block.
+    fn parse_front_matter<'a>(&self, lines: &'a [u8], start: usize) -> Option<FileContext<'a>> {
+        if start != 0 || !self.starts_file {
+            return None;
+        }
 
-                    let stats = language.parse_from_slice(
-                        style_contents.trim_first_and_last_line_of_whitespace(),
-                        config,
-                    );
-                    Some(FileContext::new(
-                        LanguageContext::Html { language },
-                        end_of_code,
-                        stats,
-                    ))
-                } else if let Some(mut captures) = html.start_template_in_range(start, end) {
-                    let start_of_code = captures.next().unwrap().end();
-                    let closing_tag = END_TEMPLATE.find(&lines[start_of_code..])?;
-                    let end_of_code = start_of_code + closing_tag.start();
-                    let language = captures
-                        .next()
-                        .and_then(|m| {
-                            LanguageType::from_str(
-                                &String::from_utf8_lossy(m.as_bytes().trim()).to_lowercase(),
-                            )
-                            .ok()
-                        })
-                        .unwrap_or(LanguageType::Html);
-
-                    let template_contents = &lines[start_of_code..end_of_code];
-                    if template_contents.trim().is_empty() {
-                        return None;
-                    }
-                    let stats = language.parse_from_slice(
-                        template_contents.trim_first_and_last_line_of_whitespace(),
-                        config,
-                    );
-                    Some(FileContext::new(
-                        LanguageContext::Html { language },
-                        end_of_code,
-                        stats,
-                    ))
-                } else {
-                    None
-                }
+        let mut stepper = LineStep::new(b'\n', 0, lines.len());
+        let (_, end_of_opening) = stepper.next(lines)?;
+        let fence = lines[..end_of_opening].trim();
+        let language = match fence {
+            b"---" => LanguageType::Yaml,
+            b"+++" => LanguageType::Toml,
+            _ => return None,
+        };
+
+        while let Some((start, end)) = stepper.next(lines) {
+            let line = lines[start..end].trim();
+            if line == fence || (language == LanguageType::Yaml && line == b"...") {
+                trace!("{} FRONT MATTER", language);
+                return Some(FileContext::borrowed(
+                    LanguageContext::Markdown {
+                        balanced: true,
+                        language: Some(language),
+                    },
+                    lines,
+                    end,
+                    lines[end_of_opening..start].trim(),
+                ));
             }
         }
+
+        None
     }
 
     #[inline]
@@ -565,12 +1062,27 @@ impl SyntaxCounter {
             .find(|(s, _)| window.starts_with(s.as_bytes()))
         {
             trace!("Start Doc {:?}", start);
-            self.quote = Some(end);
+            self.quote = Some(Cow::Borrowed(end));
             self.quote_is_verbatim = false;
             self.quote_is_doc_quote = true;
+            self.quote_interpolation = self.interpolation_of(start);
             return Some(start.len());
         }
 
+        if let Some((length, end)) = self
+            .shared
+            .raw_string_literals
+            .iter()
+            .find_map(|raw| raw.open(window))
+        {
+            trace!("Start raw {:?}", String::from_utf8_lossy(&window[..length]));
+            self.quote = Some(Cow::Owned(end));
+            self.quote_is_verbatim = true;
+            self.quote_is_doc_quote = false;
+            self.quote_interpolation = None;
+            return Some(length);
+        }
+
         if let Some((start, end)) = self
             .shared
             .verbatim_string_literals
@@ -578,9 +1090,10 @@ impl SyntaxCounter {
             .find(|(s, _)| window.starts_with(s.as_bytes()))
         {
             trace!("Start verbatim {:?}", start);
-            self.quote = Some(end);
+            self.quote = Some(Cow::Borrowed(end));
             self.quote_is_verbatim = true;
             self.quote_is_doc_quote = false;
+            self.quote_interpolation = self.interpolation_of(start);
             return Some(start.len());
         }
 
@@ -591,19 +1104,98 @@ impl SyntaxCounter {
             .find(|(s, _)| window.starts_with(s.as_bytes()))
         {
             trace!("Start {:?}", start);
-            self.quote = Some(end);
+            self.quote = Some(Cow::Borrowed(end));
             self.quote_is_verbatim = false;
             self.quote_is_doc_quote = false;
+            self.quote_interpolation = self.interpolation_of(start);
             return Some(start.len());
         }
 
         None
     }
 
+    /// The start and end of interpolations within strings starting with
+    /// `quote`.
+    fn interpolation_of(&self, quote: &str) -> Option<(&'static str, &'static str)> {
+        self.shared
+            .interpolations
+            .iter()
+            .find(|(start, _, _)| *start == quote)
+            .map(|&(_, start, end)| (start, end))
+    }
+
+    /// Leaves the current string for the code of an interpolation when
+    /// `window` starts with one.
+    #[inline]
+    pub(crate) fn parse_interpolation(&mut self, window: &[u8]) -> Option<usize> {
+        let delimiters = self.quote_interpolation?;
+        if self.quote.is_none() || !window.starts_with(delimiters.0.as_bytes()) {
+            return None;
+        }
+
+        trace!("Start interpolation {:?}", delimiters.0);
+        self.interpolations.push(Interpolation {
+            quote: self.quote.take()?,
+            quote_is_doc_quote: self.quote_is_doc_quote,
+            quote_is_verbatim: self.quote_is_verbatim,
+            delimiters,
+            depth: 0,
+        });
+        self.quote_interpolation = None;
+        Some(delimiters.0.len())
+    }
+
+    /// Returns to the string that the innermost interpolation is in when
+    /// `window` starts with its end, counting the brackets opened and closed
+    /// within it until then.
+    #[inline]
+    pub(crate) fn parse_end_of_interpolation(&mut self, window: &[u8]) -> Option<usize> {
+        if self.quote.is_some() || !self.stack.is_empty() {
+            return None;
+        }
+
+        let interpolation = self.interpolations.last_mut()?;
+        let (start, end) = interpolation.delimiters;
+        if window.starts_with(end.as_bytes()) {
+            if interpolation.depth == 0 {
+                let interpolation = self.interpolations.pop()?;
+                trace!("End interpolation {:?}", end);
+                self.quote = Some(interpolation.quote);
+                self.quote_is_doc_quote = interpolation.quote_is_doc_quote;
+                self.quote_is_verbatim = interpolation.quote_is_verbatim;
+                self.quote_interpolation = Some(interpolation.delimiters);
+                return Some(end.len());
+            }
+            interpolation.depth -= 1;
+        } else if start
+            .as_bytes()
+            .last()
+            .is_some_and(|bracket| window.first() == Some(bracket))
+        {
+            interpolation.depth += 1;
+        }
+
+        None
+    }
+
+    /// Opens a heredoc when `window` starts with one, whose body starts on the
+    /// next line.
+    #[inline]
+    pub(crate) fn parse_heredoc(&mut self, window: &[u8], before: &[u8]) -> Option<usize> {
+        if !self.is_plain_mode() || self.shared.heredocs.is_empty() {
+            return None;
+        }
+
+        let (length, heredoc) = Heredoc::open(window, before, self.shared.heredocs)?;
+        trace!("Start heredoc {:?}", heredoc.terminator);
+        self.heredocs.push(heredoc);
+        Some(length)
+    }
+
     #[inline]
     pub(crate) fn parse_end_of_quote(&mut self, window: &[u8]) -> Option<usize> {
         #[allow(clippy::if_same_then_else)]
-        if self._is_string_mode() && window.starts_with(self.quote?.as_bytes()) {
+        if self._is_string_mode() && window.starts_with(self.quote.as_deref()?.as_bytes()) {
             let quote = self.quote.take().unwrap();
             trace!("End {:?}", quote);
             Some(quote.len())
@@ -611,11 +1203,14 @@ impl SyntaxCounter {
             Some(2)
         } else if !self.quote_is_verbatim
             && window.starts_with(br"\")
-            && self
+            && (self
                 .shared
                 .string_literals
                 .iter()
                 .any(|(start, _)| window[1..].starts_with(start.as_bytes()))
+                || self
+                    .quote_interpolation
+                    .is_some_and(|(start, _)| window[1..].starts_with(start.as_bytes())))
         {
             // Tell the state machine to skip the next character because it
             // has been escaped if the string isn't a verbatim string.
@@ -642,7 +1237,10 @@ impl SyntaxCounter {
                     || self.shared.allows_nested
                     || self.shared.nested_comments.contains(&(start, end))
                 {
-                    self.stack.push(end);
+                    if self.stack.is_empty() {
+                        self.doc_comment = self.starts_with_doc_comment(window);
+                    }
+                    self.stack.push(Cow::Borrowed(end));
 
                     if log_enabled!(Trace) && self.shared.allows_nested {
                         trace!("Start nested {:?}", start);
@@ -655,7 +1253,21 @@ impl SyntaxCounter {
             }
         }
 
-        None
+        if !self.stack.is_empty() && !self.shared.allows_nested {
+            return None;
+        }
+
+        let (length, end) = self
+            .shared
+            .raw_comments
+            .iter()
+            .find_map(|raw| raw.open(window))?;
+        trace!("Start raw {:?}", String::from_utf8_lossy(&window[..length]));
+        if self.stack.is_empty() {
+            self.doc_comment = self.starts_with_doc_comment(window);
+        }
+        self.stack.push(Cow::Owned(end));
+        Some(length)
     }
 
     #[inline]
@@ -663,7 +1275,7 @@ impl SyntaxCounter {
         if self
             .stack
             .last()
-            .map_or(false, |l| window.starts_with(l.as_bytes()))
+            .is_some_and(|l| window.starts_with(l.as_bytes()))
         {
             let last = self.stack.pop().unwrap();
 
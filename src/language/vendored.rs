@@ -0,0 +1,276 @@
+//! Detecting vendored and generated files, which aren't written by hand and
+//! so are left out of the statistics by default.
+
+use std::path::{Component, Path};
+
+use once_cell::sync::Lazy;
+use regex::bytes::Regex;
+
+use crate::{language::LanguageType, stats::FileKind, utils::ext::SliceExt};
+
+/// Directories containing code copied from, or installed by, other projects.
+const VENDORED_DIRECTORIES: &[&str] = &[
+    ".yarn",
+    "bower_components",
+    "carthage",
+    "godeps",
+    "jspm_packages",
+    "node_modules",
+    "pods",
+    "third-party",
+    "third_party",
+    "thirdparty",
+    "vendor",
+    "vendors",
+];
+
+/// Endings of the file names of generated files.
+const GENERATED_SUFFIXES: &[&str] = &[
+    "-min.js",
+    ".designer.cs",
+    ".freezed.dart",
+    ".g.dart",
+    ".generated.cs",
+    ".min.css",
+    ".min.js",
+    ".pb.cc",
+    ".pb.go",
+    ".pb.h",
+    ".pb.swift",
+    "_pb.js",
+    "_pb2.py",
+    "_pb2_grpc.py",
+];
+
+/// Whole file names of generated files, mostly lock files.
+const GENERATED_FILENAMES: &[&str] = &[
+    "cargo.lock",
+    "composer.lock",
+    "gemfile.lock",
+    "go.sum",
+    "npm-shrinkwrap.json",
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "yarn.lock",
+];
+
+/// How many lines at the start of a file are searched for a marker, such as
+/// `// Code generated by protoc-gen-go. DO NOT EDIT.`, which can come after a
+/// licence header.
+const HEADER_LINES: usize = 10;
+
+/// Files of languages that are usually minified are considered generated
+/// when their lines are this long on average.
+const MINIFIED_LINE_LENGTH: usize = 110;
+
+/// Get the kind of a file from its path. Only the last `depth` directories
+/// are checked, so that counting a project inside of a `vendor` directory
+/// directly still counts its files.
+pub(crate) fn from_path(path: &Path, depth: usize) -> Option<FileKind> {
+    let filename = path.file_name()?.to_string_lossy().to_lowercase();
+
+    if GENERATED_FILENAMES.contains(&&*filename)
+        || GENERATED_SUFFIXES.iter().any(|s| filename.ends_with(s))
+    {
+        return Some(FileKind::Generated);
+    }
+
+    let directories: Vec<_> = path
+        .parent()?
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect();
+
+    directories
+        .iter()
+        .skip(directories.len().saturating_sub(depth.saturating_sub(1)))
+        .any(|directory| {
+            VENDORED_DIRECTORIES
+                .iter()
+                .any(|vendored| directory.eq_ignore_ascii_case(vendored))
+        })
+        .then_some(FileKind::Vendored)
+}
+
+/// Get the kind of a file of `language` from its contents, either from a
+/// marker in its header, or from it being minified.
+pub(crate) fn from_contents(language: LanguageType, text: &[u8]) -> Option<FileKind> {
+    (has_generated_marker(language, text) || is_minified(language, text))
+        .then_some(FileKind::Generated)
+}
+
+/// Whether a comment in the header of `text` marks it as generated. Code and
+/// prose are ignored, so that e.g. a string mentioning "auto-generated" ids
+/// isn't mistaken for a marker.
+fn has_generated_marker(language: LanguageType, text: &[u8]) -> bool {
+    static MARKER: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"(?i)code generated .*do not edit|@generated\b|<auto-?generated|generated by the protocol buffer compiler|\b(?:automatically|auto-|auto)\s?generated\b|do not (?:edit|modify)\b.*\bgenerated\b|\bgenerated\b.*do not (?:edit|modify)\b",
+        )
+        .unwrap()
+    });
+
+    header_comments(language, text)
+        .into_iter()
+        .any(|comment| MARKER.is_match(comment))
+}
+
+/// The text of the comments in the first `HEADER_LINES` lines of `text`.
+/// Strings aren't skipped, as markers come before any code that has them.
+/// Empty comments, which custom languages can declare, never start one.
+fn header_comments(language: LanguageType, text: &[u8]) -> Vec<&[u8]> {
+    let line_comments: Vec<&str> = language
+        .line_comments()
+        .iter()
+        .copied()
+        .filter(|start| !start.is_empty())
+        .collect();
+    let multi_line_comments = language
+        .multi_line_comments()
+        .iter()
+        .chain(language.nested_comments())
+        .filter(|(start, end)| !start.is_empty() && !end.is_empty());
+    let find = |haystack: &[u8], needle: &str| {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle.as_bytes())
+    };
+
+    let mut comments = Vec::new();
+    let mut block_end: Option<&str> = None;
+    for line in text.split(|&b| b == b'\n').take(HEADER_LINES) {
+        let mut rest = line;
+        while !rest.is_empty() {
+            if let Some(end) = block_end {
+                let Some(i) = find(rest, end) else {
+                    comments.push(rest);
+                    break;
+                };
+                comments.push(&rest[..i]);
+                rest = &rest[i + end.len()..];
+                block_end = None;
+                continue;
+            }
+
+            let starts = line_comments
+                .iter()
+                .filter_map(|start| Some((find(rest, start)?, start.len(), None)))
+                .chain(multi_line_comments.clone().filter_map(|(start, end)| {
+                    Some((find(rest, start)?, start.len(), Some(*end)))
+                }));
+            match starts.min_by_key(|&(i, _, _)| i) {
+                Some((i, length, None)) => {
+                    comments.push(&rest[i + length..]);
+                    break;
+                }
+                Some((i, length, end)) => {
+                    rest = &rest[i + length..];
+                    block_end = end;
+                }
+                None => break,
+            }
+        }
+    }
+    comments
+}
+
+fn is_minified(language: LanguageType, text: &[u8]) -> bool {
+    if !matches!(language, LanguageType::JavaScript | LanguageType::Css) {
+        return false;
+    }
+
+    let (lines, length) = text
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.trim().is_empty())
+        .fold((0, 0), |(lines, length), line| {
+            (lines + 1, length + line.len())
+        });
+
+    lines != 0 && length / lines > MINIFIED_LINE_LENGTH
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths() {
+        assert_eq!(
+            from_path(Path::new("./vendor/lib/a.go"), 3),
+            Some(FileKind::Vendored)
+        );
+        assert_eq!(
+            from_path(Path::new("web/node_modules/x/index.js"), 4),
+            Some(FileKind::Vendored)
+        );
+        assert_eq!(from_path(Path::new("vendor/lib/a.go"), 2), None);
+        assert_eq!(from_path(Path::new("src/vendored.rs"), 2), None);
+        assert_eq!(
+            from_path(Path::new("api/service.pb.go"), 2),
+            Some(FileKind::Generated)
+        );
+        assert_eq!(
+            from_path(Path::new("static/app.min.js"), 2),
+            Some(FileKind::Generated)
+        );
+        assert_eq!(
+            from_path(Path::new("package-lock.json"), 0),
+            Some(FileKind::Generated)
+        );
+    }
+
+    #[test]
+    fn contents() {
+        assert_eq!(
+            from_contents(
+                LanguageType::Go,
+                b"// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n"
+            ),
+            Some(FileKind::Generated)
+        );
+        assert_eq!(
+            from_contents(LanguageType::Rust, b"// @generated\nfn main() {}\n"),
+            Some(FileKind::Generated)
+        );
+        assert_eq!(
+            from_contents(LanguageType::Rust, b"// Generates code.\nfn main() {}\n"),
+            None
+        );
+        assert_eq!(
+            from_contents(
+                LanguageType::C,
+                b"/*\n * Automatically generated by gen.py.\n */\nint a;\n"
+            ),
+            Some(FileKind::Generated)
+        );
+        assert_eq!(
+            from_contents(LanguageType::Python, b"ids = 'auto-generated'\n"),
+            None
+        );
+        assert_eq!(
+            from_contents(LanguageType::Markdown, b"Docs are auto generated.\n"),
+            None
+        );
+
+        let empty = crate::language::custom::register(
+            "EmptyComments",
+            &crate::config::LanguageConfig {
+                line_comment: vec![String::new()],
+                multi_line_comments: vec![(String::new(), String::new())],
+                ..Default::default()
+            },
+        );
+        assert_eq!(from_contents(empty, b"// @generated\n"), None);
+
+        let minified = format!("var a={};", "[1,2,3],".repeat(50));
+        assert_eq!(
+            from_contents(LanguageType::JavaScript, minified.as_bytes()),
+            Some(FileKind::Generated)
+        );
+        assert_eq!(from_contents(LanguageType::Json, minified.as_bytes()), None);
+    }
+}
@@ -49,16 +49,22 @@ extern crate serde;
 
 #[macro_use]
 mod utils;
+mod category;
 mod config;
 mod consts;
+mod group_by;
 mod language;
 mod sort;
 mod stats;
 
 pub use self::{
-    config::Config,
+    category::Category,
+    config::{Config, LanguageConfig, LogicalLinesConfig},
     consts::*,
-    language::{Language, LanguageType, Languages},
+    group_by::GroupBy,
+    language::{Language, LanguageGroup, LanguageSyntax, LanguageType, Languages},
     sort::Sort,
-    stats::{find_char_boundary, CodeStats, Report},
+    stats::{
+        find_char_boundary, CodeStats, FileKind, LineClass, LineKind, Report, SkipReason, Skipped,
+    },
 };
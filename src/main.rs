@@ -1,21 +1,23 @@
 #[macro_use]
 extern crate log;
 
+mod annotate;
 mod cli;
 mod cli_utils;
 mod consts;
 mod input;
 
-use std::{error::Error, io, process};
+use std::{cmp::Reverse, error::Error, io, process};
 
-use tokei::{Config, Languages, Sort};
+use tokei::{Config, GroupBy, Language, LanguageGroup, Languages, Sort};
 
 use crate::{
     cli::Cli,
     cli_utils::Printer,
     consts::{
         BLANKS_COLUMN_WIDTH, CODE_COLUMN_WIDTH, COMMENTS_COLUMN_WIDTH, FALLBACK_ROW_LEN,
-        LANGUAGE_COLUMN_WIDTH, LINES_COLUMN_WIDTH, PATH_COLUMN_WIDTH,
+        LANGUAGE_COLUMN_WIDTH, LINES_COLUMN_WIDTH, LOGICAL_COLUMN_WIDTH, MIXED_COLUMN_WIDTH,
+        PATH_COLUMN_WIDTH,
     },
     input::add_input,
 };
@@ -24,10 +26,21 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut cli = Cli::from_args();
 
     if cli.print_languages {
-        Cli::print_supported_languages()?;
+        match cli.output {
+            Some(ref format) => println!("{}", format.print_languages()?),
+            None => Cli::print_supported_languages()?,
+        }
         process::exit(0);
     }
     let config = cli.override_config(Config::from_config_files());
+
+    if let Some(ref annotate) = cli.annotate {
+        if let Err(error) = annotate::print_annotated(annotate, &config) {
+            eprintln!("Error:\n{}", error);
+            process::exit(1);
+        }
+        process::exit(0);
+    }
     let mut languages = Languages::new();
 
     if let Some(input) = cli.file_input() {
@@ -58,13 +71,22 @@ fn main() -> Result<(), Box<dyn Error>> {
         })
         .unwrap_or(FALLBACK_ROW_LEN)
         .max(FALLBACK_ROW_LEN);
+    let mixed = config.mixed == Some(true);
+    let logical = config.logical_lines == Some(true);
 
     if cli.streaming == Some(crate::cli::Streaming::Simple) {
-        println!(
+        print!(
             "#{:^LANGUAGE_COLUMN_WIDTH$} {:^PATH_COLUMN_WIDTH$} {:^LINES_COLUMN_WIDTH$} {:^CODE_COLUMN_WIDTH$} {:^COMMENTS_COLUMN_WIDTH$} {:^BLANKS_COLUMN_WIDTH$}",
             "language", "path", "lines", "code", "comments", "blanks"
         );
-        println!(
+        if mixed {
+            print!(" {:^MIXED_COLUMN_WIDTH$}", "mixed");
+        }
+        if logical {
+            print!(" {:^LOGICAL_COLUMN_WIDTH$}", "logical");
+        }
+        println!();
+        print!(
             "{:>LANGUAGE_COLUMN_WIDTH$} {:<PATH_COLUMN_WIDTH$} {:>LINES_COLUMN_WIDTH$} {:>CODE_COLUMN_WIDTH$} {:>COMMENTS_COLUMN_WIDTH$} {:>BLANKS_COLUMN_WIDTH$}",
             (0..10).map(|_| "#").collect::<String>(),
             (0..80).map(|_| "#").collect::<String>(),
@@ -73,6 +95,13 @@ fn main() -> Result<(), Box<dyn Error>> {
             (0..12).map(|_| "#").collect::<String>(),
             (0..12).map(|_| "#").collect::<String>()
         );
+        if mixed {
+            print!(" {}", (0..12).map(|_| "#").collect::<String>());
+        }
+        if logical {
+            print!(" {}", (0..12).map(|_| "#").collect::<String>());
+        }
+        println!();
     }
 
     languages.get_statistics(&input, &cli.ignored_directories(), &config);
@@ -81,13 +110,19 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
 
     if let Some(format) = cli.output {
-        print!("{}", format.print(&languages).unwrap());
+        let printed = match config.group_by {
+            Some(GroupBy::Group) => format.print_groups(&languages, &config),
+            _ => format.print(&languages, &config),
+        };
+        print!("{}", printed.unwrap());
         process::exit(0);
     }
 
     let mut printer = Printer::new(
         columns,
         cli.files,
+        mixed,
+        logical,
         io::BufWriter::new(io::stdout()),
         cli.number_format,
     );
@@ -98,6 +133,27 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     printer.print_header()?;
 
+    if config.group_by == Some(GroupBy::Group) {
+        let sort = cli.sort.or(config.sort);
+        if let Some(sort_category) = sort {
+            for (_, ref mut language) in &mut languages {
+                language.sort_by(sort_category);
+            }
+        }
+
+        let groups = languages.groups(&config);
+        let mut groups: Vec<_> = groups.iter().collect();
+        if let Some(sort) = sort {
+            sort_rows(&mut groups, sort, cli.sort_reverse);
+        }
+
+        printer.print_groups(groups.into_iter(), cli.compact, sort.is_some())?;
+        printer.print_total(&languages, &config)?;
+        printer.print_skipped(languages.skipped())?;
+
+        return Ok(());
+    }
+
     let mut is_sorted = false;
     if let Some(sort_category) = cli.sort.or(config.sort) {
         for (_, ref mut language) in &mut languages {
@@ -105,24 +161,53 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
 
         let mut languages: Vec<_> = languages.iter().collect();
-        match sort_category {
-            Sort::Blanks => languages.sort_by(|a, b| b.1.blanks.cmp(&a.1.blanks)),
-            Sort::Comments => languages.sort_by(|a, b| b.1.comments.cmp(&a.1.comments)),
-            Sort::Code => languages.sort_by(|a, b| b.1.code.cmp(&a.1.code)),
-            Sort::Files => languages.sort_by(|a, b| b.1.reports.len().cmp(&a.1.reports.len())),
-            Sort::Lines => languages.sort_by(|a, b| b.1.lines().cmp(&a.1.lines())),
-        }
+        sort_rows(&mut languages, sort_category, cli.sort_reverse);
         is_sorted = true;
-        if cli.sort_reverse {
-            printer.print_results(languages.into_iter().rev(), cli.compact, is_sorted)?;
-        } else {
-            printer.print_results(languages.into_iter(), cli.compact, is_sorted)?;
-        }
+        printer.print_results(languages.into_iter(), cli.compact, is_sorted)?;
     } else {
         printer.print_results(languages.iter(), cli.compact, is_sorted)?;
     }
 
-    printer.print_total(&languages)?;
+    printer.print_total(&languages, &config)?;
+    printer.print_skipped(languages.skipped())?;
 
     Ok(())
 }
+
+/// The columns that the rows of the output can be sorted by.
+trait SortKey {
+    fn sort_key(&self, sort: Sort) -> usize;
+}
+
+impl SortKey for Language {
+    fn sort_key(&self, sort: Sort) -> usize {
+        match sort {
+            Sort::Blanks => self.blanks,
+            Sort::Comments => self.comments,
+            Sort::Code => self.code,
+            Sort::Files => self.reports.len(),
+            Sort::Lines => self.lines(),
+        }
+    }
+}
+
+impl SortKey for LanguageGroup {
+    fn sort_key(&self, sort: Sort) -> usize {
+        match sort {
+            Sort::Blanks => self.blanks,
+            Sort::Comments => self.comments,
+            Sort::Code => self.code,
+            Sort::Files => self.files(),
+            Sort::Lines => self.lines(),
+        }
+    }
+}
+
+/// Sorts rows of `(name, stats)` from the largest to the smallest in the
+/// `sort` column, or the other way around with `reverse`.
+fn sort_rows<N, S: SortKey>(rows: &mut [(N, &S)], sort: Sort, reverse: bool) {
+    rows.sort_by_key(|(_, stats)| Reverse(stats.sort_key(sort)));
+    if reverse {
+        rows.reverse();
+    }
+}
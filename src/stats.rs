@@ -14,6 +14,20 @@ pub struct CodeStats {
     pub code: usize,
     /// The lines of comments in the blob.
     pub comments: usize,
+    /// The lines of comments in the blob that are documentation, e.g. `///`
+    /// or `/** */`, from the `doc_line_comment` and `doc_multi_line_comments`
+    /// of the language. These lines are also counted in `comments`.
+    #[serde(default)]
+    pub docs: usize,
+    /// The lines of code in the blob that also contain a comment, e.g.
+    /// `let x = 1; // one`. These lines are also counted in `code`, and are
+    /// only counted when enabled with `Config.mixed`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mixed: Option<usize>,
+    /// The logical lines in the blob, i.e. statements, when enabled with
+    /// `Config.logical_lines` for a language with a `logical_lines` syntax.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logical: Option<usize>,
     /// Language blobs that were contained inside this blob.
     pub blobs: BTreeMap<LanguageType, CodeStats>,
 }
@@ -42,7 +56,10 @@ impl CodeStats {
 
             summary.blanks += child_summary.blanks;
             summary.comments += child_summary.comments;
+            summary.docs += child_summary.docs;
             summary.code += child_summary.code;
+            add_optional(&mut summary.mixed, child_summary.mixed);
+            add_optional(&mut summary.logical, child_summary.logical);
         }
 
         summary
@@ -60,6 +77,9 @@ impl ops::AddAssign<&'_ CodeStats> for CodeStats {
         self.blanks += rhs.blanks;
         self.code += rhs.code;
         self.comments += rhs.comments;
+        self.docs += rhs.docs;
+        add_optional(&mut self.mixed, rhs.mixed);
+        add_optional(&mut self.logical, rhs.logical);
 
         for (language, stats) in &rhs.blobs {
             *self.blobs.entry(*language).or_default() += stats;
@@ -67,6 +87,153 @@ impl ops::AddAssign<&'_ CodeStats> for CodeStats {
     }
 }
 
+/// Adds an optional count, e.g. of mixed lines, which is only `None` when
+/// neither side was counted.
+pub(crate) fn add_optional(count: &mut Option<usize>, rhs: Option<usize>) {
+    if let Some(rhs) = rhs {
+        *count = Some(count.unwrap_or(0) + rhs);
+    }
+}
+
+/// How a line was counted, see [`LineClass`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum LineKind {
+    /// A line of code, counted in `CodeStats.code`.
+    Code,
+    /// A comment, counted in `CodeStats.comments`.
+    Comment,
+    /// A blank line, counted in `CodeStats.blanks`.
+    Blank,
+}
+
+/// How a single line was counted by [`LanguageType::classify_lines`].
+///
+/// [`LanguageType::classify_lines`]: enum.LanguageType.html#method.classify_lines
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LineClass {
+    /// The number of the line in the text, starting from 1. A line can be
+    /// listed more than once when it's counted in more than one language,
+    /// e.g. `<script>let a = 1;</script>`, and lines that aren't counted,
+    /// such as blank lines around embedded code, aren't listed.
+    pub line: usize,
+    /// Whether the line is code, a comment, or blank.
+    pub kind: LineKind,
+    /// The embedded language the line was counted in, e.g. JavaScript within
+    /// a `<script>` tag of HTML, or `None` for the language itself.
+    pub language: Option<LanguageType>,
+    /// Whether a line of code also contains a comment, see
+    /// `CodeStats.mixed`.
+    #[serde(default)]
+    pub mixed: bool,
+    /// Whether a comment is documentation, see `CodeStats.docs`.
+    #[serde(default)]
+    pub doc: bool,
+    /// The number of logical lines that end on a line of code, see
+    /// `CodeStats.logical`.
+    #[serde(default)]
+    pub logical: usize,
+}
+
+impl LineClass {
+    /// Creates a new `LineClass` of the language itself.
+    #[must_use]
+    pub fn new(line: usize, kind: LineKind) -> Self {
+        Self {
+            line,
+            kind,
+            language: None,
+            mixed: false,
+            doc: false,
+            logical: 0,
+        }
+    }
+}
+
+/// Why a file isn't considered to be code written by hand. Files of each
+/// kind are only counted when enabled with `Config.vendored`,
+/// `Config.generated`, or `Config.documentation`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum FileKind {
+    /// Code copied from other projects, e.g. in a `vendor` directory.
+    Vendored,
+    /// Code written by a program, e.g. minified JavaScript, or files marked
+    /// `DO NOT EDIT`.
+    Generated,
+    /// Documentation, marked with `linguist-documentation` in
+    /// `.gitattributes`.
+    Documentation,
+}
+
+/// Why a file was skipped without being counted, see [`Skipped`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// The file has the extension of a language, but its contents are binary,
+    /// e.g. an MPEG transport stream named `.ts`.
+    Binary,
+    /// The file is vendored, and `Config.vendored` isn't set.
+    Vendored,
+    /// The file is generated, and `Config.generated` isn't set.
+    Generated,
+    /// The file is documentation, and `Config.documentation` isn't set.
+    Documentation,
+}
+
+impl SkipReason {
+    /// The reason files of `kind` are skipped when they aren't counted.
+    pub(crate) fn for_kind(kind: FileKind) -> Self {
+        match kind {
+            FileKind::Vendored => SkipReason::Vendored,
+            FileKind::Generated => SkipReason::Generated,
+            FileKind::Documentation => SkipReason::Documentation,
+        }
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SkipReason::Binary => "binary",
+            SkipReason::Vendored => "vendored",
+            SkipReason::Generated => "generated",
+            SkipReason::Documentation => "documentation",
+        })
+    }
+}
+
+/// A file that was matched to a language but left out of the statistics,
+/// see [`Languages::skipped`].
+///
+/// [`Languages::skipped`]: struct.Languages.html#method.skipped
+#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[non_exhaustive]
+pub struct Skipped {
+    /// Why the file was skipped.
+    pub reason: SkipReason,
+    /// File name.
+    pub name: PathBuf,
+}
+
+impl Skipped {
+    /// Creates a new `Skipped` for the file `name`.
+    #[must_use]
+    pub fn new(reason: SkipReason, name: PathBuf) -> Self {
+        Self { reason, name }
+    }
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "skipped: {} {}", self.reason, self.name.display())
+    }
+}
+
 /// A struct representing the statistics of a file.
 #[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
 #[non_exhaustive]
@@ -75,6 +242,13 @@ pub struct Report {
     pub stats: CodeStats,
     /// File name.
     pub name: PathBuf,
+    /// Whether the file is vendored, generated, or documentation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<FileKind>,
+    /// The name of the encoding that the file was decoded from, e.g. `UTF-8`
+    /// or `Shift_JIS`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
 }
 
 impl Report {
@@ -1,12 +1,17 @@
 use std::{collections::BTreeMap, path::Path};
 
+use globset::{GlobSet, GlobSetBuilder};
 use ignore::{overrides::OverrideBuilder, DirEntry, WalkBuilder, WalkState::Continue};
-
 use rayon::prelude::*;
 
+use super::{
+    gitattributes::{Attributes, GitAttributes},
+    glob::glob,
+};
 use crate::{
     config::Config,
-    language::{Language, LanguageType},
+    language::{binary::BinaryFile, vendored, Language, LanguageType},
+    stats::{FileKind, SkipReason, Skipped},
 };
 
 const IGNORE_FILE: &str = ".tokeignore";
@@ -16,8 +21,9 @@ pub fn get_all_files<A: AsRef<Path>>(
     ignored_directories: &[&str],
     languages: &mut BTreeMap<LanguageType, Language>,
     config: &Config,
-) {
+) -> Vec<Skipped> {
     let languages = parking_lot::Mutex::new(languages);
+    let skipped = parking_lot::Mutex::new(Vec::new());
     let (tx, rx) = crossbeam_channel::unbounded();
 
     let mut paths = paths.iter();
@@ -77,7 +83,7 @@ pub fn get_all_files<A: AsRef<Path>>(
                 }
             };
 
-            if entry.file_type().map_or(false, |ft| ft.is_file()) {
+            if entry.file_type().is_some_and(|ft| ft.is_file()) {
                 tx.send(entry).unwrap();
             }
 
@@ -85,13 +91,56 @@ pub fn get_all_files<A: AsRef<Path>>(
         })
     });
 
-    let rx_iter = rx
-        .into_iter()
-        .par_bridge()
-        .filter_map(|e| LanguageType::from_path(e.path(), config).map(|l| (e, l)));
+    let gitattributes = GitAttributes::new();
+    let rx_iter = rx.into_iter().par_bridge().filter_map(|e| {
+        let attributes = gitattributes.for_path(e.path(), e.depth());
+        attributes
+            .language
+            .or_else(|| LanguageType::from_path(e.path(), config))
+            .map(|l| (e, l, attributes))
+    });
+
+    // Whether files of `kind` are skipped, recording why they were.
+    let skip_kind = |kind: Option<FileKind>, path: &Path| {
+        if config.is_kind_counted(kind) {
+            return false;
+        }
+        if let Some(kind) = kind {
+            let reason = SkipReason::for_kind(kind);
+            skipped.lock().push(Skipped::new(reason, path.to_owned()));
+        }
+        true
+    };
+
+    let process = |(entry, language, attributes): (DirEntry, LanguageType, Attributes)| {
+        // Vendored and generated files are skipped by their path before being
+        // read where possible, and by their contents otherwise.
+        let path_kind = attributes.apply(vendored::from_path(entry.path(), entry.depth()));
+        if skip_kind(path_kind, entry.path()) {
+            return;
+        }
+
+        let result = language.parse(entry.into_path(), config).map(|mut stats| {
+            stats.kind = attributes.apply(path_kind.or(stats.kind));
+            stats
+        });
+        if result
+            .as_ref()
+            .is_ok_and(|stats| skip_kind(stats.kind, &stats.name))
+        {
+            return;
+        }
+
+        if let Err((ref error, ref path)) = result {
+            if BinaryFile::is(error) {
+                debug!("Skipping binary file {}", path.display());
+                skipped
+                    .lock()
+                    .push(Skipped::new(SkipReason::Binary, path.clone()));
+                return;
+            }
+        }
 
-    let process = |(entry, language): (DirEntry, LanguageType)| {
-        let result = language.parse(entry.into_path(), config);
         let mut lock = languages.lock();
         let entry = lock.entry(language).or_insert_with(Language::new);
         match result {
@@ -109,11 +158,11 @@ pub fn get_all_files<A: AsRef<Path>>(
         }
     };
 
-    if let Some(types) = config.types.as_deref() {
-        rx_iter.filter(|(_, l)| types.contains(l)).for_each(process)
-    } else {
-        rx_iter.for_each(process)
-    }
+    rx_iter
+        .filter(|(_, language, _)| config.is_counted(*language))
+        .for_each(process);
+
+    skipped.into_inner()
 }
 
 pub(crate) fn get_extension(path: &Path) -> Option<String> {
@@ -124,16 +173,34 @@ pub(crate) fn get_filename(path: &Path) -> Option<String> {
     path.file_name().map(|e| e.to_string_lossy().to_lowercase())
 }
 
+/// Compiles glob `patterns` into a [`GlobSet`], see [`glob`](super::glob::glob). Invalid
+/// patterns are skipped.
+pub(crate) fn glob_set(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        match glob(pattern, false) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(error) => error!("Invalid glob {:?}: {}", pattern, error),
+        }
+    }
+
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
 #[cfg(test)]
 mod tests {
-    use std::fs;
+    use std::{fs, path::Path};
 
     use tempfile::TempDir;
 
     use super::IGNORE_FILE;
     use crate::{
-        config::Config,
+        config::{Config, LanguageConfig},
         language::{languages::Languages, LanguageType},
+        stats::{FileKind, SkipReason, Skipped},
     };
 
     const FILE_CONTENTS: &[u8] = b"fn main() {}";
@@ -483,4 +550,264 @@ mod tests {
 
         assert!(languages.get(LANGUAGE).is_some());
     }
+
+    #[test]
+    fn custom_language() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let mut languages = Languages::new();
+
+        let mut acme = LanguageConfig::new();
+        acme.line_comment = vec![String::from("#")];
+        acme.extensions(vec![String::from("acme")]);
+        let config = Config {
+            languages: Some(std::iter::once((String::from("Acme"), acme)).collect()),
+            ..Config::default()
+        };
+
+        fs::write(dir.path().join("main.acme"), "# comment\nprint 1\n").unwrap();
+
+        super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+
+        let language = LanguageType::from_name("Acme").expect("Acme wasn't registered.");
+        let acme = languages.get(&language).expect("main.acme wasn't counted.");
+        assert_eq!(acme.reports.len(), 1);
+        assert_eq!(acme.reports[0].stats.code, 1);
+        assert_eq!(acme.reports[0].stats.comments, 1);
+    }
+
+    #[test]
+    fn remapped_language_ignores_case() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let mut languages = Languages::new();
+
+        let mut python = LanguageConfig::new();
+        python.extensions(vec![String::from("foo")]);
+        let config = Config {
+            languages: Some(std::iter::once((String::from("python"), python)).collect()),
+            ..Config::default()
+        };
+
+        fs::write(dir.path().join("main.foo"), "# c\nx = 1\n").unwrap();
+
+        super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+
+        let python = languages
+            .get(&LanguageType::Python)
+            .expect("main.foo wasn't counted as Python.");
+        assert_eq!(python.reports.len(), 1);
+        assert_eq!(python.reports[0].stats.comments, 1);
+        assert_eq!(languages.len(), 1);
+    }
+
+    #[test]
+    fn vendored() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let mut config = Config::default();
+        let mut languages = Languages::new();
+
+        fs::create_dir(dir.path().join("vendor")).unwrap();
+        fs::write(dir.path().join("vendor").join(FILE_NAME), FILE_CONTENTS).unwrap();
+
+        let skipped = super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+
+        assert!(languages.get(LANGUAGE).is_none());
+        assert_eq!(
+            skipped,
+            [Skipped::new(
+                SkipReason::Vendored,
+                dir.path().join("vendor").join(FILE_NAME)
+            )]
+        );
+
+        config.vendored = Some(true);
+
+        super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+
+        let rust = languages.get(LANGUAGE).expect("vendor/ wasn't counted.");
+        assert_eq!(rust.reports[0].kind, Some(FileKind::Vendored));
+    }
+
+    #[test]
+    fn generated() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let mut config = Config::default();
+        let mut languages = Languages::new();
+
+        fs::write(
+            dir.path().join(FILE_NAME),
+            "// @generated by build.rs\nfn main() {}\n",
+        )
+        .unwrap();
+
+        let skipped = super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+
+        assert!(languages.get(LANGUAGE).is_none());
+        assert_eq!(
+            skipped,
+            [Skipped::new(
+                SkipReason::Generated,
+                dir.path().join(FILE_NAME)
+            )]
+        );
+
+        config.generated = Some(true);
+
+        super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+
+        let rust = languages
+            .get(LANGUAGE)
+            .expect("Generated file wasn't counted.");
+        assert_eq!(rust.reports[0].kind, Some(FileKind::Generated));
+    }
+
+    #[test]
+    fn gitattributes() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let mut languages = Languages::new();
+
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::create_dir(dir.path().join("vendor")).unwrap();
+        fs::create_dir(dir.path().join("docs")).unwrap();
+        fs::write(
+            dir.path().join(".gitattributes"),
+            "*.inc linguist-language=Rust\n\
+             vendor/** -linguist-vendored\n\
+             docs/** linguist-documentation\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("main.inc"), FILE_CONTENTS).unwrap();
+        fs::write(dir.path().join("vendor").join(FILE_NAME), FILE_CONTENTS).unwrap();
+        fs::write(dir.path().join("docs").join(FILE_NAME), FILE_CONTENTS).unwrap();
+
+        super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &Config::default(),
+        );
+
+        let rust = languages
+            .get(LANGUAGE)
+            .expect("No Rust files were counted.");
+        let mut names: Vec<_> = rust
+            .reports
+            .iter()
+            .map(|report| report.name.strip_prefix(dir.path()).unwrap())
+            .collect();
+        names.sort();
+
+        assert_eq!(
+            names,
+            [Path::new("main.inc"), &Path::new("vendor").join(FILE_NAME)]
+        );
+    }
+
+    #[test]
+    fn gitattributes_above_the_walk() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let project = dir.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(
+            dir.path().join(".gitattributes"),
+            "*.rs linguist-vendored\n",
+        )
+        .unwrap();
+        fs::write(project.join(FILE_NAME), FILE_CONTENTS).unwrap();
+
+        let count = || {
+            let mut languages = Languages::new();
+            super::get_all_files(
+                &[project.to_str().unwrap()],
+                &[],
+                &mut languages,
+                &Config::default(),
+            );
+            languages.get(LANGUAGE).map_or(0, |l| l.reports.len())
+        };
+
+        // Outside of a repository, nothing above the walk is read.
+        assert_eq!(count(), 1);
+
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        assert_eq!(count(), 0);
+    }
+
+    #[test]
+    fn binary() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let mut languages = Languages::new();
+
+        fs::write(dir.path().join(FILE_NAME), FILE_CONTENTS).unwrap();
+        fs::write(dir.path().join("video.ts"), b"\x47\x40\x00\x10\x00\x00\xB0").unwrap();
+
+        languages.get_statistics(&[dir.path()], &[], &Config::default());
+
+        assert!(languages.get(&LanguageType::TypeScript).is_none());
+        assert_eq!(languages[LANGUAGE].reports.len(), 1);
+        assert_eq!(
+            languages.skipped(),
+            &[Skipped::new(
+                SkipReason::Binary,
+                dir.path().join("video.ts")
+            )]
+        );
+    }
+
+    #[test]
+    fn encodings() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let config = Config::default();
+
+        // The second byte of `表` in Shift_JIS is a `\`, escaping the quote.
+        let mut contents = b"fn main() {\n    let a = \"".to_vec();
+        contents.extend(b"\x95\x5C");
+        contents.extend(b"\";\n    // comment\n}\n");
+        fs::write(dir.path().join(FILE_NAME), contents).unwrap();
+
+        let mut languages = Languages::new();
+        languages.get_statistics(&[dir.path()], &[], &config);
+        let rust = &languages[LANGUAGE];
+        assert_eq!(rust.comments, 0);
+        assert_eq!(rust.reports[0].encoding.as_deref(), Some("UTF-8"));
+
+        let config = Config {
+            encodings: Some([(String::from("*.rs"), String::from("sjis"))].into()),
+            ..Config::default()
+        };
+        let mut languages = Languages::new();
+        languages.get_statistics(&[dir.path()], &[], &config);
+        let rust = &languages[LANGUAGE];
+        assert_eq!(rust.comments, 1);
+        assert_eq!(rust.reports[0].encoding.as_deref(), Some("Shift_JIS"));
+    }
 }
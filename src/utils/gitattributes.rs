@@ -0,0 +1,318 @@
+//! Reading the linguist attributes of files from `.gitattributes`, so that
+//! files are classified the same way as on GitHub.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use dashmap::DashMap;
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::{language::LanguageType, stats::FileKind};
+
+const ATTRIBUTES_FILE: &str = ".gitattributes";
+
+/// The linguist attributes of a file. Each attribute is `None` when it's
+/// unspecified, leaving it to tokei's own detection.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Attributes {
+    /// `linguist-language`
+    pub language: Option<LanguageType>,
+    /// `linguist-vendored`
+    pub vendored: Option<bool>,
+    /// `linguist-generated`
+    pub generated: Option<bool>,
+    /// `linguist-documentation`
+    pub documentation: Option<bool>,
+}
+
+impl Attributes {
+    /// Applies the attributes to the `kind` that tokei detected for the file.
+    /// Setting an attribute overrides the detected kind, while unsetting one,
+    /// e.g. with `-linguist-vendored`, only undoes that kind.
+    pub(crate) fn apply(&self, kind: Option<FileKind>) -> Option<FileKind> {
+        if self.documentation == Some(true) {
+            return Some(FileKind::Documentation);
+        } else if self.vendored == Some(true) {
+            return Some(FileKind::Vendored);
+        } else if self.generated == Some(true) {
+            return Some(FileKind::Generated);
+        }
+
+        match kind {
+            Some(FileKind::Documentation) if self.documentation == Some(false) => None,
+            Some(FileKind::Vendored) if self.vendored == Some(false) => None,
+            Some(FileKind::Generated) if self.generated == Some(false) => None,
+            kind => kind,
+        }
+    }
+
+    fn assign(&mut self, attribute: &str) {
+        let (name, value) = match attribute.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (attribute, None),
+        };
+        let (name, state) = if let Some(name) = name.strip_prefix('-') {
+            (name, Some(false))
+        } else if let Some(name) = name.strip_prefix('!') {
+            (name, None)
+        } else {
+            (name, Some(value != Some("false")))
+        };
+
+        match name {
+            "linguist-language" => {
+                self.language = value.and_then(|value| {
+                    value
+                        .parse()
+                        .ok()
+                        .or_else(|| LanguageType::from_alias(&value.to_lowercase()))
+                        .or_else(|| {
+                            warn!(
+                                "Unknown linguist-language in {}: {}",
+                                ATTRIBUTES_FILE, value
+                            );
+                            None
+                        })
+                })
+            }
+            "linguist-vendored" => self.vendored = state,
+            "linguist-generated" => self.generated = state,
+            "linguist-documentation" => self.documentation = state,
+            _ => {}
+        }
+    }
+}
+
+/// A line of a `.gitattributes` file with at least one linguist attribute.
+#[derive(Debug)]
+struct Rule {
+    pattern: GlobMatcher,
+    attributes: Vec<String>,
+}
+
+impl Rule {
+    /// Parses a line, where patterns without a `/` match files in any
+    /// directory, and other patterns are relative to the `.gitattributes`.
+    fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        let pattern = words
+            .next()
+            .filter(|p| !p.starts_with(['#', '!', '[', '"']))?;
+        let attributes: Vec<_> = words
+            .filter(|a| a.trim_start_matches(['-', '!']).starts_with("linguist-"))
+            .map(String::from)
+            .collect();
+
+        if attributes.is_empty() {
+            return None;
+        }
+
+        let pattern = if pattern.trim_end_matches('/').contains('/') {
+            pattern.trim_start_matches('/').to_owned()
+        } else {
+            format!("**/{}", pattern)
+        };
+
+        match GlobBuilder::new(&pattern).literal_separator(true).build() {
+            Ok(glob) => Some(Self {
+                pattern: glob.compile_matcher(),
+                attributes,
+            }),
+            Err(error) => {
+                warn!("Invalid pattern in {}: {}", ATTRIBUTES_FILE, error);
+                None
+            }
+        }
+    }
+}
+
+/// The rules of a `.gitattributes` file, as seen from a directory below it.
+#[derive(Clone, Debug)]
+struct Scope {
+    /// The path of the directory relative to the `.gitattributes` file.
+    relative: PathBuf,
+    rules: Arc<[Rule]>,
+}
+
+/// The `.gitattributes` files that apply to the files of a walk, read once
+/// for each directory and only kept for as long as the walk.
+#[derive(Debug, Default)]
+pub(crate) struct GitAttributes {
+    directories: DashMap<PathBuf, Arc<[Scope]>>,
+}
+
+impl GitAttributes {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the linguist attributes of the file at `path`, `depth` levels below
+    /// the root of the walk, from the `.gitattributes` files in its directory
+    /// and its parents. Parents above the walk are only read up to the root of
+    /// the repository containing it. Like git, the last matching line wins,
+    /// and files in deeper directories take precedence.
+    pub(crate) fn for_path(&self, path: &Path, depth: usize) -> Attributes {
+        let mut attributes = Attributes::default();
+        let Some(filename) = path.file_name() else {
+            return attributes;
+        };
+        let parent = match path.parent() {
+            Some(parent) if parent != Path::new("") => parent,
+            _ => Path::new("."),
+        };
+
+        for scope in self.scopes(parent, depth.saturating_sub(1)).iter() {
+            let relative = scope.relative.join(filename);
+
+            for rule in scope.rules.iter() {
+                if rule.pattern.is_match(&relative) {
+                    for attribute in &rule.attributes {
+                        attributes.assign(attribute);
+                    }
+                }
+            }
+        }
+
+        attributes
+    }
+
+    /// The scopes that apply to files in `directory`, `depth` levels below the
+    /// root of the walk, from the outermost to `directory`'s own.
+    fn scopes(&self, directory: &Path, depth: usize) -> Arc<[Scope]> {
+        if let Some(scopes) = self.directories.get(directory) {
+            return scopes.clone();
+        }
+
+        let mut scopes = if directory.join(".git").exists() {
+            Vec::new()
+        } else if depth == 0 {
+            enclosing_repository(directory)
+        } else {
+            let parent = match directory.parent() {
+                Some(parent) if parent != Path::new("") => parent,
+                _ => Path::new("."),
+            };
+            let name = directory
+                .file_name()
+                .map(Path::new)
+                .unwrap_or(Path::new(""));
+            self.scopes(parent, depth - 1)
+                .iter()
+                .map(|scope| Scope {
+                    relative: scope.relative.join(name),
+                    rules: scope.rules.clone(),
+                })
+                .collect()
+        };
+        scopes.extend(read_rules(directory).map(|rules| Scope {
+            relative: PathBuf::new(),
+            rules,
+        }));
+
+        let scopes: Arc<[Scope]> = scopes.into();
+        self.directories
+            .insert(directory.to_owned(), scopes.clone());
+        scopes
+    }
+}
+
+fn read_rules(directory: &Path) -> Option<Arc<[Rule]>> {
+    fs::read_to_string(directory.join(ATTRIBUTES_FILE))
+        .ok()
+        .map(|contents| contents.lines().filter_map(Rule::parse).collect())
+}
+
+/// The scopes of the directories above a root of the walk, when it's inside
+/// of a git repository. Outside of one, nothing above the walk is read.
+fn enclosing_repository(root: &Path) -> Vec<Scope> {
+    let Ok(root) = fs::canonicalize(root) else {
+        return Vec::new();
+    };
+    let Some(repository) = root
+        .ancestors()
+        .skip(1)
+        .position(|ancestor| ancestor.join(".git").exists())
+    else {
+        return Vec::new();
+    };
+
+    root.ancestors()
+        .skip(1)
+        .take(repository + 1)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .filter_map(|ancestor| {
+            Some(Scope {
+                relative: root.strip_prefix(ancestor).ok()?.to_owned(),
+                rules: read_rules(ancestor)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes(line: &str, path: &str) -> Attributes {
+        let rule = Rule::parse(line).expect("Line has no linguist attributes.");
+        let mut attributes = Attributes::default();
+
+        if rule.pattern.is_match(path) {
+            for attribute in &rule.attributes {
+                attributes.assign(attribute);
+            }
+        }
+
+        attributes
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!(
+            attributes("*.inc linguist-language=PHP", "src/lib.inc").language,
+            Some(LanguageType::Php)
+        );
+        assert_eq!(
+            attributes("docs/** linguist-documentation", "docs/a/b.md").documentation,
+            Some(true)
+        );
+        assert_eq!(
+            attributes("docs/** linguist-documentation", "src/docs/b.md").documentation,
+            None
+        );
+        assert_eq!(
+            attributes("/gen/*.rs -linguist-generated", "gen/a.rs").generated,
+            Some(false)
+        );
+        assert_eq!(
+            attributes("*.js linguist-vendored=false", "a.js").vendored,
+            Some(false)
+        );
+        assert!(Rule::parse("*.rs text eol=lf").is_none());
+        assert!(Rule::parse("# *.rs linguist-vendored").is_none());
+    }
+
+    #[test]
+    fn apply() {
+        let vendored = Attributes {
+            vendored: Some(true),
+            ..Attributes::default()
+        };
+        let not_vendored = Attributes {
+            vendored: Some(false),
+            ..Attributes::default()
+        };
+
+        assert_eq!(vendored.apply(None), Some(FileKind::Vendored));
+        assert_eq!(not_vendored.apply(Some(FileKind::Vendored)), None);
+        assert_eq!(
+            not_vendored.apply(Some(FileKind::Generated)),
+            Some(FileKind::Generated)
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! Building globs that match whole paths. `build.rs` includes this module
+//! too, so that the globs of `languages.json` are checked exactly as they're
+//! built at runtime.
+
+use std::borrow::Cow;
+
+use globset::{Glob, GlobBuilder};
+
+/// Compiles a glob `pattern` matching whole paths. Patterns that don't start
+/// with `/` or `**` can match at any depth, so `src/**` matches both
+/// `./src/main.rs` and `/home/alice/project/src/main.rs`.
+pub(crate) fn glob(pattern: &str, case_insensitive: bool) -> Result<Glob, globset::Error> {
+    let pattern = if pattern.starts_with('/') || pattern.starts_with("**") {
+        Cow::Borrowed(pattern)
+    } else {
+        Cow::Owned(format!("**/{}", pattern))
+    };
+
+    GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .case_insensitive(case_insensitive)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directory_globs() {
+        let matcher = glob(".github/workflows/*.yml", true)
+            .unwrap()
+            .compile_matcher();
+        assert!(matcher.is_match(".github/workflows/ci.yml"));
+        assert!(matcher.is_match("./project/.github/workflows/CI.yml"));
+        assert!(!matcher.is_match(".github/workflows/old/ci.yml"));
+        assert!(!matcher.is_match(".github/ci.yml"));
+
+        let matcher = glob("**/CMakeLists.txt", false).unwrap().compile_matcher();
+        assert!(matcher.is_match("CMakeLists.txt"));
+        assert!(matcher.is_match("src/lib/CMakeLists.txt"));
+    }
+}
@@ -2,3 +2,5 @@
 mod macros;
 pub(crate) mod ext;
 pub mod fs;
+pub(crate) mod gitattributes;
+pub(crate) mod glob;
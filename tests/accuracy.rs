@@ -26,45 +26,162 @@ macro_rules! get_digit {
     }};
 }
 
+/// The files in `tests/data` are marked with `linguist-documentation` in
+/// `.gitattributes`, so that GitHub doesn't count them.
+fn test_config() -> Config {
+    Config {
+        documentation: Some(true),
+        ..Config::default()
+    }
+}
+
 mod config {
+    use super::test_config;
     use tokei::*;
 
-    /*
     #[test]
     fn extension_change() {
-        use std::collections::HashMap;
+        use std::collections::BTreeMap;
         let mut languages = Languages::new();
         let config = Config {
             languages: {
-                let mut map = HashMap::new();
+                let mut map = BTreeMap::new();
                 let mut config = LanguageConfig::new();
                 config.extensions(vec![String::from("cpp")]);
-                map.insert(LanguageType::C, config);
+                map.insert(String::from("C"), config);
 
                 Some(map)
             },
-            ..Config::default()
+            ..test_config()
         };
 
         languages.get_statistics(&["tests/data/cpp.cpp"], &[], &config);
 
         if languages.len() != 1 {
-            panic!("wrong languages detected: expected just C, found {:?}",
-                   languages.into_iter().collect::<Vec<_>>());
+            panic!(
+                "wrong languages detected: expected just C, found {:?}",
+                languages.into_iter().collect::<Vec<_>>()
+            );
         }
 
         let (name, _) = languages.into_iter().next().unwrap();
 
         assert_eq!(LanguageType::C, name);
     }
-    */
+
+    #[test]
+    fn extension_change_outside_paths() {
+        use std::collections::BTreeMap;
+        let mut languages = Languages::new();
+        let config = Config {
+            languages: {
+                let mut map = BTreeMap::new();
+                let mut config = LanguageConfig::new();
+                config.extensions(vec![String::from("cpp")]);
+                config.paths(vec![String::from("tests/embedding/**")]);
+                map.insert(String::from("C"), config);
+
+                Some(map)
+            },
+            ..test_config()
+        };
+
+        languages.get_statistics(&["tests/data/cpp.cpp"], &[], &config);
+
+        if languages.len() != 1 {
+            panic!(
+                "wrong languages detected: expected just C++, found {:?}",
+                languages.into_iter().collect::<Vec<_>>()
+            );
+        }
+
+        let (name, _) = languages.into_iter().next().unwrap();
+
+        assert_eq!(LanguageType::Cpp, name);
+    }
+
+    #[test]
+    fn extension_change_within_paths() {
+        use std::collections::BTreeMap;
+        let mut languages = Languages::new();
+        let config = Config {
+            languages: {
+                let mut map = BTreeMap::new();
+                let mut config = LanguageConfig::new();
+                config.extensions(vec![String::from("cpp")]);
+                config.paths(vec![String::from("tests/data/**")]);
+                map.insert(String::from("C"), config);
+
+                Some(map)
+            },
+            ..test_config()
+        };
+
+        languages.get_statistics(&["tests/data/cpp.cpp"], &[], &config);
+
+        if languages.len() != 1 {
+            panic!(
+                "wrong languages detected: expected just C, found {:?}",
+                languages.into_iter().collect::<Vec<_>>()
+            );
+        }
+
+        let (name, _) = languages.into_iter().next().unwrap();
+
+        assert_eq!(LanguageType::C, name);
+    }
+
+    #[test]
+    fn exclude_categories() {
+        let mut languages = Languages::new();
+        let config = Config {
+            exclude_categories: Some(vec![Category::Data]),
+            ..test_config()
+        };
+
+        languages.get_statistics(
+            &["tests/data/rust.rs", "tests/data/yaml.yaml"],
+            &[],
+            &config,
+        );
+
+        if languages.len() != 1 {
+            panic!(
+                "wrong languages detected: expected just Rust, found {:?}",
+                languages.into_iter().collect::<Vec<_>>()
+            );
+        }
+
+        let (name, _) = languages.into_iter().next().unwrap();
+
+        assert_eq!(LanguageType::Rust, name);
+    }
+
+    #[test]
+    fn total_programming_only() {
+        let mut languages = Languages::new();
+        let config = Config {
+            total_programming_only: Some(true),
+            ..test_config()
+        };
+
+        languages.get_statistics(
+            &["tests/data/rust.rs", "tests/data/yaml.yaml"],
+            &[],
+            &config,
+        );
+
+        assert_eq!(languages.len(), 2);
+        assert_eq!(languages.total_for(&config).lines(), 48);
+        assert!(languages.total().lines() > 48);
+    }
 
     #[test]
     fn treating_comments_as_code() {
         let mut languages = Languages::new();
         let config = Config {
             treat_doc_strings_as_comments: Some(true),
-            ..Config::default()
+            ..test_config()
         };
 
         languages.get_statistics(&["tests/data/python.py"], &[], &config);
@@ -85,4 +202,36 @@ mod config {
     }
 }
 
+#[test]
+fn documentation_is_skipped_by_default() {
+    use tokei::{SkipReason, Skipped};
+
+    let dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir(dir.path().join(".git")).unwrap();
+    fs::create_dir(dir.path().join("docs")).unwrap();
+    fs::write(
+        dir.path().join(".gitattributes"),
+        "docs/* linguist-documentation\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+    fs::write(dir.path().join("docs").join("example.rs"), "fn main() {}\n").unwrap();
+
+    let mut languages = Languages::new();
+    languages.get_statistics(&[dir.path()], &[], &Config::default());
+    assert_eq!(languages.total().code, 1);
+    assert_eq!(
+        languages.skipped(),
+        [Skipped::new(
+            SkipReason::Documentation,
+            dir.path().join("docs").join("example.rs")
+        )]
+    );
+
+    let mut languages = Languages::new();
+    languages.get_statistics(&[dir.path()], &[], &test_config());
+    assert_eq!(languages.total().code, 2);
+    assert!(languages.skipped().is_empty());
+}
+
 include!(concat!(env!("OUT_DIR"), "/tests.rs"));
@@ -0,0 +1,11 @@
+// 11 lines 6 code 3 comments 2 blanks
+#pragma once
+
+#include <string>
+
+/* A named thing. */
+class Named {
+public:
+    // Returns the name.
+    std::string name() const;
+};
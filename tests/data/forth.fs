@@ -0,0 +1,8 @@
+\ 8 lines 4 code 2 comments 2 blanks
+: square ( n -- n*n )
+    dup * ;
+
+( Prints the square of 5 )
+5 square .
+
+: cube dup square * ;
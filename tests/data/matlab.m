@@ -0,0 +1,10 @@
+% 10 lines 4 code 5 comments 1 blanks
+function y = square(x)
+    % Squares its input.
+    y = x .^ 2;
+end
+
+%{
+Block comments
+%}
+disp("100% done")
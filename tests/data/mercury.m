@@ -0,0 +1,9 @@
+% 9 lines 6 code 1 comments 2 blanks
+:- module hello.
+:- interface.
+:- import_module io.
+
+:- pred main(io::di, io::uo) is det.
+
+:- implementation.
+main(!IO) :- io.write_string("Hello, % world\n", !IO).
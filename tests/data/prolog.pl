@@ -0,0 +1,10 @@
+% 10 lines 5 code 3 comments 2 blanks
+:- module(family, [parent/2]).
+
+/* Facts about
+   a family */
+parent(tom, bob).
+parent(bob, ann).
+
+grandparent(X, Z) :-
+    parent(X, Y), parent(Y, Z).
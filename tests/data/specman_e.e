@@ -0,0 +1,8 @@
+// 8 lines 4 code 4 comments 0 blanks
+<'
+struct packet {
+    -- the payload
+    data : uint;
+};
+'>
+Text outside of code blocks is a comment.
@@ -0,0 +1,9 @@
+// 9 lines 4 code 3 comments 2 blanks
+`timescale 1ns / 1ps
+
+/* A simple AND gate */
+module and_gate(input a, input b, output y);
+    assign y = a & b;
+
+endmodule
+// "quoted" text